@@ -11,9 +11,9 @@ async fn main() -> Result<()> {
         .await?;
     let block = block.unwrap();
 
-    if block.transactions.len() > 0 {
+    if !block.transactions.is_empty() {
         let transaction = client
-            .transaction_by_hash(String::from("test"), block.transactions[0].hash)
+            .transaction_by_hash(String::from("test"), block.transactions[0])
             .await?;
         println!("{:?}", transaction);
     }
@@ -0,0 +1,61 @@
+//! Serves an in-memory Juice node for integration tests.
+//!
+//! ```text
+//! juice-mock-node [--addr ADDR] [--ledger NAME]... [--fund ADDRESS=VON]... [--gas-price VON]
+//! ```
+
+use juice_sdk_rs::{mock_node::MockNode, types::U256};
+use std::{net::SocketAddr, process};
+
+const USAGE: &str = "usage: juice-mock-node [--addr ADDR] [--ledger NAME]... \
+                     [--fund ADDRESS=VON]... [--gas-price VON]";
+
+fn parse_amount(amount: &str) -> U256 {
+    U256::from_dec_str(amount).unwrap_or_else(|_| exit(&format!("invalid amount: {}", amount)))
+}
+
+fn exit(message: &str) -> ! {
+    eprintln!("{}\n{}", message, USAGE);
+    process::exit(2)
+}
+
+#[tokio::main]
+async fn main() -> juice_sdk_rs::Result<()> {
+    let mut addr: SocketAddr = "127.0.0.1:7009".parse().expect("valid address");
+    let mut node = MockNode::new();
+
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .unwrap_or_else(|| exit(&format!("missing value for {}", arg)))
+        };
+        match arg.as_str() {
+            "--addr" => {
+                let value = value();
+                addr = value
+                    .parse()
+                    .unwrap_or_else(|_| exit(&format!("invalid address: {}", value)));
+            }
+            "--ledger" => node = node.ledger(&value()),
+            "--fund" => {
+                let value = value();
+                let (address, amount) = value
+                    .split_once('=')
+                    .unwrap_or_else(|| exit(&format!("invalid funding: {}", value)));
+                node = node.fund(address, parse_amount(amount));
+            }
+            "--gas-price" => node = node.gas_price(parse_amount(&value())),
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return Ok(());
+            }
+            _ => exit(&format!("unknown argument: {}", arg)),
+        }
+    }
+
+    let server = node.serve(addr)?;
+    println!("Listening on {}", server.url());
+    tokio::signal::ctrl_c().await?;
+    Ok(())
+}
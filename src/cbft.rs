@@ -0,0 +1,452 @@
+//! CBFT quorum certificates.
+//!
+//! The `extraData` of a block starts with 32 bytes of vanity data and the
+//! 65 bytes of the proposer's seal. It is followed, once the block was
+//! proposed on top of a certified parent, by the RLP encoded quorum
+//! certificate (QC) of the parent: the aggregate BLS signature of the
+//! validators which voted for it.
+//!
+//! The pairing check is left to a `BlsVerifier`, so any BLS12-381
+//! implementation matching the node's can be plugged in. The `bls` feature
+//! provides `Bls12381`, backed by `blst`.
+
+use crate::{
+    error::{Error, Result},
+    helpers,
+    types::{BlockHeader, BlsPublic, Bytes, CbftNode, Ledger, H256},
+};
+use rlp::{Rlp, RlpStream};
+
+/// Length of the vanity data and the proposer's seal in `extraData`.
+pub const EXTRA_SEAL_LEN: usize = 32 + 65;
+
+/// Verifies BLS aggregate signatures.
+pub trait BlsVerifier {
+    /// Returns true if `signature` is the aggregate signature of `message`
+    /// by every given public key.
+    fn verify_aggregate(&self, keys: &[BlsPublic], message: &[u8], signature: &[u8]) -> bool;
+}
+
+/// Domain separation tag of the hash to curve used by `Bls12381` by default.
+#[cfg(feature = "bls")]
+pub const DEFAULT_DST: &[u8] = b"BLS_SIG_BLS12381G1_XMD:SHA-256_SSWU_RO_NUL_";
+
+/// BLS12-381 verifier with 96 bytes public keys in G2 and 48 bytes
+/// signatures in G1, both compressed.
+#[cfg(feature = "bls")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Bls12381 {
+    dst: Vec<u8>,
+}
+
+#[cfg(feature = "bls")]
+impl Default for Bls12381 {
+    fn default() -> Self {
+        Self::with_dst(DEFAULT_DST)
+    }
+}
+
+#[cfg(feature = "bls")]
+impl Bls12381 {
+    /// Create a verifier hashing messages with the given domain separation
+    /// tag.
+    pub fn with_dst(dst: &[u8]) -> Self {
+        Bls12381 { dst: dst.to_vec() }
+    }
+}
+
+#[cfg(feature = "bls")]
+impl BlsVerifier for Bls12381 {
+    fn verify_aggregate(&self, keys: &[BlsPublic], message: &[u8], signature: &[u8]) -> bool {
+        use blst::{
+            min_sig::{PublicKey, Signature},
+            BLST_ERROR,
+        };
+
+        let signature = match Signature::from_bytes(signature) {
+            Ok(signature) => signature,
+            Err(_) => return false,
+        };
+        let keys = match keys
+            .iter()
+            .map(|key| PublicKey::key_validate(key.as_bytes()))
+            .collect::<std::result::Result<Vec<_>, _>>()
+        {
+            Ok(keys) if !keys.is_empty() => keys,
+            _ => return false,
+        };
+        let keys = keys.iter().collect::<Vec<_>>();
+        signature.fast_aggregate_verify(true, message, &self.dst, &keys) == BLST_ERROR::BLST_SUCCESS
+    }
+}
+
+/// Validators which signed a quorum certificate, by index in the
+/// consensus set.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidatorSet {
+    /// Number of validators
+    pub bits: u32,
+    /// Bits of the signers, 64 validators per element
+    pub elems: Vec<u64>,
+}
+
+impl ValidatorSet {
+    /// Create a set of `bits` validators, marking the given signers.
+    pub fn new(bits: u32, signers: impl IntoIterator<Item = usize>) -> Self {
+        let mut set = ValidatorSet {
+            bits,
+            elems: vec![0; (bits as usize).div_ceil(64)],
+        };
+        for idx in signers.into_iter().filter(|idx| *idx < bits as usize) {
+            set.elems[idx / 64] |= 1 << (idx % 64);
+        }
+        set
+    }
+
+    /// Returns true if the validator with the given index signed.
+    pub fn contains(&self, idx: usize) -> bool {
+        idx < self.bits as usize
+            && self
+                .elems
+                .get(idx / 64)
+                .is_some_and(|elem| elem & (1 << (idx % 64)) != 0)
+    }
+
+    /// Returns the indices of the signers.
+    pub fn signers(&self) -> Vec<usize> {
+        (0..self.bits as usize)
+            .filter(|idx| self.contains(*idx))
+            .collect()
+    }
+}
+
+/// Quorum certificate of a block.
+#[derive(Debug, Clone, PartialEq)]
+pub struct QuorumCert {
+    /// Epoch of the vote
+    pub epoch: u64,
+    /// View of the vote
+    pub view_number: u64,
+    /// Hash of the certified block
+    pub block_hash: H256,
+    /// Number of the certified block
+    pub block_number: u64,
+    /// Index of the block in the view
+    pub block_index: u32,
+    /// Aggregate BLS signature of the signers
+    pub signature: Bytes,
+    /// Signers of the certificate
+    pub validator_set: ValidatorSet,
+}
+
+impl QuorumCert {
+    /// Decodes a RLP encoded certificate.
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        let decode = || -> std::result::Result<Self, rlp::DecoderError> {
+            let rlp = Rlp::new(bytes);
+            let set = rlp.at(6)?;
+            Ok(QuorumCert {
+                epoch: rlp.val_at(0)?,
+                view_number: rlp.val_at(1)?,
+                block_hash: rlp.val_at(2)?,
+                block_number: rlp.val_at(3)?,
+                block_index: rlp.val_at(4)?,
+                signature: Bytes(rlp.val_at(5)?),
+                validator_set: ValidatorSet {
+                    bits: set.val_at(0)?,
+                    elems: set.list_at(1)?,
+                },
+            })
+        };
+        decode().map_err(|e| Error::Decoder(format!("invalid quorum certificate: {}", e)))
+    }
+
+    /// Extracts the certificate of the parent from the header's
+    /// `extraData`, `None` if the block does not carry one.
+    pub fn from_header(header: &BlockHeader) -> Result<Option<Self>> {
+        match header.extra.0.get(EXTRA_SEAL_LEN..) {
+            Some(qc) if !qc.is_empty() => Self::decode(qc).map(Some),
+            _ => Ok(None),
+        }
+    }
+
+    /// Encodes the certificate with RLP.
+    pub fn rlp_bytes(&self) -> Vec<u8> {
+        let mut stream = RlpStream::new_list(7);
+        stream
+            .append(&self.epoch)
+            .append(&self.view_number)
+            .append(&self.block_hash)
+            .append(&self.block_number)
+            .append(&self.block_index)
+            .append(&self.signature.0)
+            .begin_list(2)
+            .append(&self.validator_set.bits)
+            .append_list(&self.validator_set.elems);
+        stream.out().to_vec()
+    }
+
+    /// Returns the message signed by the validators, the keccak256 hash of
+    /// the RLP encoded epoch, view, block hash, number and index.
+    pub fn signing_bytes(&self) -> [u8; 32] {
+        let mut stream = RlpStream::new_list(5);
+        stream
+            .append(&self.epoch)
+            .append(&self.view_number)
+            .append(&self.block_hash)
+            .append(&self.block_number)
+            .append(&self.block_index);
+        helpers::keccak256(&stream.out())
+    }
+}
+
+/// Verifies quorum certificates against a consensus set.
+#[derive(Debug, Clone)]
+pub struct QcVerifier<V> {
+    validators: Vec<BlsPublic>,
+    verifier: V,
+}
+
+impl<V: BlsVerifier> QcVerifier<V> {
+    /// Create a verifier for the given consensus nodes, in consensus order.
+    pub fn new(consensus: &[CbftNode], verifier: V) -> Self {
+        QcVerifier {
+            validators: consensus.iter().map(|node| node.bls_pub_key).collect(),
+            verifier,
+        }
+    }
+
+    /// Create a verifier for the consensus nodes of the ledger.
+    pub fn from_ledger(ledger: &Ledger, verifier: V) -> Self {
+        Self::new(&ledger.consensus, verifier)
+    }
+
+    /// Returns the number of signers required for a quorum, more than two
+    /// thirds of the validators.
+    pub fn threshold(&self) -> usize {
+        self.validators.len() * 2 / 3 + 1
+    }
+
+    /// Checks that a quorum of validators signed the certificate.
+    pub fn verify(&self, qc: &QuorumCert) -> Result {
+        let set = &qc.validator_set;
+        if set.bits as usize != self.validators.len() {
+            return Err(Error::Verification(format!(
+                "validator set of {} bits, {} validators expected",
+                set.bits,
+                self.validators.len()
+            )));
+        }
+        let signers = set.signers();
+        if signers.len() < self.threshold() {
+            return Err(Error::Verification(format!(
+                "{} signers, {} required",
+                signers.len(),
+                self.threshold()
+            )));
+        }
+        let keys = signers
+            .iter()
+            .map(|idx| self.validators[*idx])
+            .collect::<Vec<_>>();
+        if !self
+            .verifier
+            .verify_aggregate(&keys, &qc.signing_bytes(), &qc.signature.0)
+        {
+            return Err(Error::Verification(format!(
+                "invalid aggregate signature of block {:?}",
+                qc.block_hash
+            )));
+        }
+        Ok(())
+    }
+
+    /// Checks the certificate carried by the header, which must certify its
+    /// parent. Returns the certificate.
+    pub fn verify_header(&self, header: &BlockHeader) -> Result<QuorumCert> {
+        let qc = QuorumCert::from_header(header)?.ok_or_else(|| {
+            Error::Verification(format!("block {:?} without certificate", header.hash))
+        })?;
+        let parent_number = header.number.unwrap_or_default().as_u64().checked_sub(1);
+        if qc.block_hash != header.parent_hash || Some(qc.block_number) != parent_number {
+            return Err(Error::Verification(format!(
+                "certificate of block {:?} does not certify the parent of {:?}",
+                qc.block_hash, header.hash
+            )));
+        }
+        self.verify(&qc)?;
+        Ok(qc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Fake BLS scheme, the signature is the hash of the keys and message.
+    #[derive(Debug, Clone)]
+    struct HashVerifier;
+
+    impl HashVerifier {
+        fn sign(keys: &[BlsPublic], message: &[u8]) -> Vec<u8> {
+            let data = keys
+                .iter()
+                .flat_map(|key| key.as_bytes().to_vec())
+                .chain(message.iter().copied())
+                .collect::<Vec<_>>();
+            helpers::keccak256(&data).to_vec()
+        }
+    }
+
+    impl BlsVerifier for HashVerifier {
+        fn verify_aggregate(&self, keys: &[BlsPublic], message: &[u8], signature: &[u8]) -> bool {
+            Self::sign(keys, message) == signature
+        }
+    }
+
+    fn consensus() -> Vec<CbftNode> {
+        (0..4)
+            .map(|idx| CbftNode {
+                node: format!("node{}", idx),
+                bls_pub_key: BlsPublic::repeat_byte(idx + 1),
+            })
+            .collect()
+    }
+
+    fn signed_qc(signers: &[usize]) -> QuorumCert {
+        let mut qc = QuorumCert {
+            epoch: 1,
+            view_number: 7,
+            block_hash: H256::repeat_byte(0xaa),
+            block_number: 9,
+            block_index: 2,
+            signature: Bytes::default(),
+            validator_set: ValidatorSet::new(4, signers.iter().copied()),
+        };
+        let keys = signers
+            .iter()
+            .map(|idx| consensus()[*idx].bls_pub_key)
+            .collect::<Vec<_>>();
+        qc.signature = HashVerifier::sign(&keys, &qc.signing_bytes()).into();
+        qc
+    }
+
+    fn header(qc: &QuorumCert) -> BlockHeader {
+        let extra = [vec![0; EXTRA_SEAL_LEN], qc.rlp_bytes()].concat();
+        serde_json::from_value(serde_json::json!({
+            "parentHash": qc.block_hash,
+            "miner": helpers::bytes_to_address("lax", &Default::default()),
+            "stateRoot": H256::zero(),
+            "transactionsRoot": H256::zero(),
+            "receiptsRoot": H256::zero(),
+            "logsBloom": crate::types::H2048::zero(),
+            "number": "0xa",
+            "gasLimit": "0x0",
+            "gasUsed": "0x0",
+            "timestamp": "0x0",
+            "extraData": Bytes(extra),
+            "nonce": "0x",
+            "hash": H256::repeat_byte(0xbb),
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn should_track_signers() {
+        let set = ValidatorSet::new(70, vec![0, 3, 64, 69, 70]);
+
+        assert_eq!(set.elems, vec![0b1001, 0b100001]);
+        assert_eq!(set.signers(), vec![0, 3, 64, 69]);
+        assert!(!set.contains(70));
+    }
+
+    #[test]
+    fn should_verify_header_certificate() {
+        // given
+        let verifier = QcVerifier::new(&consensus(), HashVerifier);
+        let qc = signed_qc(&[0, 1, 3]);
+
+        // when
+        let verified = verifier.verify_header(&header(&qc));
+
+        // then
+        assert_eq!(verified, Ok(qc.clone()));
+        assert_eq!(verifier.threshold(), 3);
+        assert_eq!(QuorumCert::decode(&qc.rlp_bytes()), Ok(qc));
+    }
+
+    #[test]
+    fn should_reject_invalid_certificates() {
+        let verifier = QcVerifier::new(&consensus(), HashVerifier);
+        let failure = |qc: &QuorumCert| matches!(verifier.verify(qc), Err(Error::Verification(_)));
+
+        // not enough signers
+        assert!(failure(&signed_qc(&[0, 1])));
+        // forged signers
+        let mut qc = signed_qc(&[0, 1, 2]);
+        qc.validator_set = ValidatorSet::new(4, vec![0, 1, 3]);
+        assert!(failure(&qc));
+        // other consensus set
+        let mut qc = signed_qc(&[0, 1, 2]);
+        qc.validator_set.bits = 5;
+        assert!(failure(&qc));
+        // other block
+        let mut qc = signed_qc(&[0, 1, 2]);
+        let mut header = header(&qc);
+        header.parent_hash = H256::zero();
+        assert!(verifier.verify_header(&header).is_err());
+        qc.block_hash = H256::zero();
+        assert!(failure(&qc));
+        // no certificate
+        header.extra = vec![0; EXTRA_SEAL_LEN].into();
+        assert_eq!(QuorumCert::from_header(&header), Ok(None));
+        assert!(verifier.verify_header(&header).is_err());
+    }
+
+    #[cfg(feature = "bls")]
+    #[test]
+    fn should_verify_bls_certificate() {
+        use blst::min_sig::{AggregateSignature, SecretKey};
+
+        // given
+        let keys = (0u8..4)
+            .map(|idx| SecretKey::key_gen(&[idx + 1; 32], &[]).unwrap())
+            .collect::<Vec<_>>();
+        let consensus = keys
+            .iter()
+            .enumerate()
+            .map(|(idx, key)| CbftNode {
+                node: format!("node{}", idx),
+                bls_pub_key: BlsPublic::from_slice(&key.sk_to_pk().compress()),
+            })
+            .collect::<Vec<_>>();
+        let sign = |qc: &mut QuorumCert, signers: &[usize]| {
+            let message = qc.signing_bytes();
+            let signatures = signers
+                .iter()
+                .map(|idx| keys[*idx].sign(&message, DEFAULT_DST, &[]))
+                .collect::<Vec<_>>();
+            let signatures = signatures.iter().collect::<Vec<_>>();
+            let aggregate = AggregateSignature::aggregate(&signatures, true).unwrap();
+            qc.signature = aggregate.to_signature().compress().to_vec().into();
+        };
+        let mut qc = signed_qc(&[0, 1, 3]);
+        sign(&mut qc, &[0, 1, 3]);
+        let verifier = QcVerifier::new(&consensus, Bls12381::default());
+
+        // then
+        assert_eq!(qc.signature.0.len(), 48);
+        assert_eq!(verifier.verify_header(&header(&qc)), Ok(qc.clone()));
+        // signed by other validators than claimed
+        let mut forged = qc.clone();
+        sign(&mut forged, &[0, 1, 2]);
+        assert!(verifier.verify(&forged).is_err());
+        // other view
+        let mut forged = qc.clone();
+        forged.view_number += 1;
+        assert!(verifier.verify(&forged).is_err());
+        // other domain
+        let verifier = QcVerifier::new(&consensus, Bls12381::with_dst(b"other"));
+        assert!(verifier.verify(&qc).is_err());
+    }
+}
@@ -0,0 +1,161 @@
+use crate::{
+    helpers::{self, CallFuture},
+    types::{NodeInfo, PeerInfo},
+    Transport,
+};
+
+/// `admin` namespace of the node, to manage its peers.
+#[derive(Debug, Clone)]
+pub struct Admin<T: Transport> {
+    transport: T,
+}
+
+impl<T: Transport> Admin<T> {
+    /// Create a new `admin` namespace client.
+    pub fn new(transport: T) -> Self {
+        Self { transport }
+    }
+
+    /// Returns the underlying transport.
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    /// Returns information about the node running the ledger.
+    pub fn node_info(&self, ledger: String) -> CallFuture<NodeInfo, T::Out> {
+        let ledger = helpers::serialize(&ledger);
+        CallFuture::new(self.transport.execute("admin_nodeInfo", vec![ledger]))
+    }
+
+    /// Returns the peers connected on the ledger.
+    pub fn peers(&self, ledger: String) -> CallFuture<Vec<PeerInfo>, T::Out> {
+        let ledger = helpers::serialize(&ledger);
+        CallFuture::new(self.transport.execute("admin_peers", vec![ledger]))
+    }
+
+    /// Connects to the peer with the given enode URL, returns true if the
+    /// peer was accepted.
+    pub fn add_peer(&self, ledger: String, enode: String) -> CallFuture<bool, T::Out> {
+        let ledger = helpers::serialize(&ledger);
+        let enode = helpers::serialize(&enode);
+        CallFuture::new(self.transport.execute("admin_addPeer", vec![ledger, enode]))
+    }
+
+    /// Disconnects from the peer with the given enode URL, returns true if
+    /// the peer was known.
+    pub fn remove_peer(&self, ledger: String, enode: String) -> CallFuture<bool, T::Out> {
+        let ledger = helpers::serialize(&ledger);
+        let enode = helpers::serialize(&enode);
+        CallFuture::new(
+            self.transport
+                .execute("admin_removePeer", vec![ledger, enode]),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Admin;
+    use crate::helpers::tests::TestTransport;
+    use serde_json::json;
+
+    #[test]
+    fn should_get_peers() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.set_response(json!([{
+            "id": "a1b2",
+            "name": "Juice/v1.1.0/linux-amd64/go1.16",
+            "enode": "enode://a1b2@10.0.0.2:16789",
+            "caps": ["juice/1"],
+            "network": {
+                "localAddress": "10.0.0.1:16789",
+                "remoteAddress": "10.0.0.2:16789",
+                "inbound": false,
+                "trusted": false,
+                "static": true
+            },
+            "protocols": {"juice": {"version": 1}}
+        }]));
+        let admin = Admin::new(&transport);
+
+        // when
+        let peers = futures::executor::block_on(admin.peers("sys".into())).unwrap();
+
+        // then
+        transport.assert_request("admin_peers", &[r#""sys""#.into()]);
+        transport.assert_no_more_requests();
+        assert_eq!(peers.len(), 1);
+        assert_eq!(peers[0].network.remote_address, "10.0.0.2:16789");
+        assert!(peers[0].network.is_static);
+        assert_eq!(peers[0].protocols["juice"]["version"], 1);
+    }
+
+    #[test]
+    fn should_get_node_info() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.set_response(json!({
+            "id": "c3d4",
+            "name": "Juice/v1.1.0/linux-amd64/go1.16",
+            "enode": "enode://c3d4@10.0.0.1:16789",
+            "ip": "10.0.0.1",
+            "ports": {"discovery": 16789, "listener": 16789},
+            "listenAddr": "[::]:16789",
+            "protocols": {"juice": {"network": 201030}}
+        }));
+        let admin = Admin::new(&transport);
+
+        // when
+        let info = futures::executor::block_on(admin.node_info("sys".into())).unwrap();
+
+        // then
+        transport.assert_request("admin_nodeInfo", &[r#""sys""#.into()]);
+        transport.assert_no_more_requests();
+        assert_eq!(info.enode, "enode://c3d4@10.0.0.1:16789");
+        assert_eq!(info.enr, None);
+        assert_eq!(info.ports.listener, 16789);
+        assert_eq!(info.listen_addr, "[::]:16789");
+        assert_eq!(info.protocols["juice"]["network"], 201030);
+    }
+
+    #[test]
+    fn should_add_peer() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.set_response(json!(true));
+        let admin = Admin::new(&transport);
+
+        // when
+        let added = admin.add_peer("sys".into(), "enode://a1b2@10.0.0.2:16789".into());
+        let added = futures::executor::block_on(added);
+
+        // then
+        transport.assert_request(
+            "admin_addPeer",
+            &[r#""sys""#.into(), r#""enode://a1b2@10.0.0.2:16789""#.into()],
+        );
+        transport.assert_no_more_requests();
+        assert_eq!(added, Ok(true));
+    }
+
+    #[test]
+    fn should_remove_peer() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.set_response(json!(false));
+        let admin = Admin::new(&transport);
+
+        // when
+        let removed = admin.remove_peer("sys".into(), "enode://a1b2@10.0.0.2:16789".into());
+        let removed = futures::executor::block_on(removed);
+
+        // then
+        transport.assert_request(
+            "admin_removePeer",
+            &[r#""sys""#.into(), r#""enode://a1b2@10.0.0.2:16789""#.into()],
+        );
+        transport.assert_no_more_requests();
+        assert_eq!(removed, Ok(false));
+    }
+}
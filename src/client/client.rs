@@ -1,8 +1,11 @@
 use crate::{
+    client::{Admin, Debug, LedgerClient},
+    error::{Error, Result},
     helpers::{self, CallFuture},
     types::{
-        Address, Block, BlockHeader, BlockNumber, Bytes, CallRequest, Index, SyncState,
-        Transaction, TransactionReceipt, TransactionRequest, H256, U256, U64,
+        Address, Block, BlockHeader, BlockNumber, Bytes, CallRequest, Index, Proof, ReceiptStatus,
+        RevertReason, SyncState, Transaction, TransactionReceipt, TransactionRequest, H256, U256,
+        U64,
     },
     Transport,
 };
@@ -11,6 +14,7 @@ use crate::{
 #[derive(Debug, Clone)]
 pub struct Client<T: Transport> {
     transport: T,
+    #[allow(dead_code)]
     is_http: bool,
 }
 
@@ -20,6 +24,61 @@ impl<T: Transport> Client<T> {
         Self { transport, is_http }
     }
 
+    /// Returns the underlying transport.
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    /// Returns the `admin` namespace, sharing the transport.
+    pub fn admin(&self) -> Admin<T> {
+        Admin::new(self.transport.clone())
+    }
+
+    /// Returns the `debug` namespace, sharing the transport.
+    pub fn debug(&self) -> Debug<T> {
+        Debug::new(self.transport.clone())
+    }
+
+    /// Returns a client bound to the given ledger.
+    pub fn ledger(&self, ledger: impl Into<String>) -> LedgerClient<T> {
+        LedgerClient::new(self.clone(), ledger.into())
+    }
+
+    /// Returns the network id of the node.
+    pub fn network_id(&self) -> CallFuture<String, T::Out> {
+        CallFuture::new(self.transport.execute("net_version", vec![]))
+    }
+
+    /// Returns the number of peers connected to the node.
+    pub fn peer_count(&self) -> CallFuture<U64, T::Out> {
+        CallFuture::new(self.transport.execute("net_peerCount", vec![]))
+    }
+
+    /// Returns true if the node is listening for peer connections.
+    pub fn is_listening(&self) -> CallFuture<bool, T::Out> {
+        CallFuture::new(self.transport.execute("net_listening", vec![]))
+    }
+
+    /// Returns the version of the node software.
+    pub fn client_version(&self) -> CallFuture<String, T::Out> {
+        CallFuture::new(self.transport.execute("web3_clientVersion", vec![]))
+    }
+
+    /// Returns the chain id used to sign the transactions of the ledger.
+    pub fn chain_id(&self, ledger: String) -> CallFuture<U64, T::Out> {
+        let ledger = helpers::serialize(&ledger);
+        CallFuture::new(self.transport.execute("juice_chainId", vec![ledger]))
+    }
+
+    /// Returns the version of the protocol spoken by the node.
+    pub fn protocol_version(&self, ledger: String) -> CallFuture<U64, T::Out> {
+        let ledger = helpers::serialize(&ledger);
+        CallFuture::new(
+            self.transport
+                .execute("juice_protocolVersion", vec![ledger]),
+        )
+    }
+
     /// Get the current block number from chain.
     pub fn block_number(&self, ledger: String) -> CallFuture<U64, T::Out> {
         let ledger = helpers::serialize(&ledger);
@@ -122,7 +181,7 @@ impl<T: Transport> Client<T> {
     }
 
     /// Returns the total number of transactions in the given block.
-    pub fn transaction_count(&self, ledger: String, block_hash: H256) -> CallFuture<u32, T::Out> {
+    pub fn transaction_count(&self, ledger: String, block_hash: H256) -> CallFuture<U64, T::Out> {
         let ledger = helpers::serialize(&ledger);
         let block_hash = helpers::serialize(&block_hash);
 
@@ -149,6 +208,40 @@ impl<T: Transport> Client<T> {
         ))
     }
 
+    /// Returns the total number of transactions in the block with the given
+    /// number.
+    pub fn transaction_count_by_number(
+        &self,
+        ledger: String,
+        number: BlockNumber,
+    ) -> CallFuture<U64, T::Out> {
+        let ledger = helpers::serialize(&ledger);
+        let number = helpers::serialize(&number);
+
+        CallFuture::new(self.transport.execute(
+            "juice_getBlockTransactionCountByNumber",
+            vec![ledger, number],
+        ))
+    }
+
+    /// Returns a single transaction at index in the block with the given
+    /// number.
+    pub fn transaction_in_block_by_number(
+        &self,
+        ledger: String,
+        number: BlockNumber,
+        index: Index,
+    ) -> CallFuture<Option<Transaction>, T::Out> {
+        let ledger = helpers::serialize(&ledger);
+        let number = helpers::serialize(&number);
+        let index = helpers::serialize(&index);
+
+        CallFuture::new(self.transport.execute(
+            "juice_getTransactionByBlockNumberAndIndex",
+            vec![ledger, number, index],
+        ))
+    }
+
     /// Returns the receipt of a transaction by transaction hash.
     pub fn transaction_receipt(
         &self,
@@ -206,6 +299,26 @@ impl<T: Transport> Client<T> {
         )
     }
 
+    /// Returns the account and storage proofs of the given account, see
+    /// `Proof::verify` to check them against the block's state root.
+    pub fn proof(
+        &self,
+        ledger: String,
+        account: Address,
+        keys: Vec<H256>,
+        block: Option<BlockNumber>,
+    ) -> CallFuture<Proof, T::Out> {
+        let ledger = helpers::serialize(&ledger);
+        let account = helpers::serialize(&account);
+        let keys = helpers::serialize(&keys);
+        let number = helpers::serialize(&block.unwrap_or(BlockNumber::Latest));
+
+        CallFuture::new(
+            self.transport
+                .execute("juice_getProof", vec![ledger, account, keys, number]),
+        )
+    }
+
     /// Returns the contract code of the given account.
     pub fn code_at(
         &self,
@@ -304,24 +417,67 @@ impl<T: Transport> Client<T> {
                 .execute("juice_sendRawTransaction", vec![ledger, rlp]),
         )
     }
+
+    /// Recovers the revert reason of a failed transaction, by replaying it
+    /// with `juice_call` on the state of the parent of its block.
+    ///
+    /// The transactions preceding it in the same block are not replayed, so
+    /// the reason may differ if it depends on them.
+    ///
+    /// Returns `None` if the receipt is not failed, or if the replayed call
+    /// does not revert.
+    pub async fn revert_reason(
+        &self,
+        ledger: String,
+        receipt: &TransactionReceipt,
+    ) -> Result<Option<RevertReason>> {
+        if receipt.status != Some(ReceiptStatus::Failure) {
+            return Ok(None);
+        }
+        let tx = self
+            .transaction_by_hash(ledger.clone(), receipt.transaction_hash)
+            .await?
+            .ok_or_else(|| {
+                Error::InvalidResponse(format!(
+                    "transaction {:?} not found",
+                    receipt.transaction_hash
+                ))
+            })?;
+        let block = tx
+            .block_number
+            .or(receipt.block_number)
+            .map(|number| BlockNumber::Number(number.saturating_sub(U64::one())))
+            .unwrap_or(BlockNumber::Latest);
+        let req = CallRequest {
+            from: tx.from,
+            to: tx.to,
+            gas: Some(tx.gas),
+            gas_price: Some(tx.gas_price),
+            value: tx.value,
+            data: Some(tx.input),
+        };
+        match self.call(ledger, req, block).await {
+            Ok(_) => Ok(None),
+            Err(err) => match err.revert_reason() {
+                Some(reason) => Ok(Some(reason)),
+                None => Err(err),
+            },
+        }
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use serde_json::json;
-
     use crate::rpc::Value;
-    use crate::types::{
-        Address, Block, BlockId, BlockNumber, Bytes, CallRequest, Log, SyncState, Transaction,
-        TransactionId, TransactionReceipt, TransactionRequest, H256, H520, H64,
-    };
+    use crate::types::{Address, Block, BlockNumber, CallRequest, Transaction, H256, U64};
 
-    // response for RPC juice_getBlockByHash/juice_getBlockByNumber
-    const EXAMPLE_BLOCK: &'static str = r#"{
+    // response for RPC juice_getBlockByHash/juice_getBlockByNumber, its hash
+    // was computed outside of the crate from the RLP of the header fields
+    const EXAMPLE_BLOCK: &str = r#"{
         "extraData": "0x00000000000000000000000000000000000000000000000000000000000000008e4ffcc4c25d36a28e18db26fba22f7b76304c07b58037931c77ea98f24fccd175a60d6a36a9f81bc363b4d605963c6c3d8eaebdba86b87a2c4080d914d1a2d601",
         "gasLimit": "0x111",
         "gasUsed": "0x0",
-        "hash": "0xddb59c26e1d77e82276c2a2d5eec61cae3d85bcdc20106f4e71f3555bbeebb6a",
+        "hash": "0x15a16185fd69bccfc703db650b792235ee4eaa3ee447ffc9c7ea4b8da9818b57",
         "logsBloom": "0x00000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000000",
         "miner": "juc1683skg34pc9cz6ks6whf7fcg3pt7nj3sezr770",
         "nonce": "0x02fb3522c488b843956c31b1def2f8ef17d4c12f8a669b29aca8ac866d74a1be4417e589c27fe4425f4b67ed174362636916bd48535ad853d85bc8ce8b0c9fd8afc7ebf003a3221c4aafeb4171bde8944d",
@@ -337,6 +493,54 @@ mod tests {
 
     use super::Client;
 
+    rpc_test!(
+        Client:network_id => "net_version";
+        Value::String("201018".into()) => "201018"
+    );
+
+    rpc_test!(
+        Client:peer_count => "net_peerCount";
+        Value::String("0x19".into()) => 0x19
+    );
+
+    rpc_test!(
+        Client:is_listening => "net_listening";
+        Value::Bool(true) => true
+    );
+
+    rpc_test!(
+        Client:client_version => "web3_clientVersion";
+        Value::String("Juice/v1.1.0/linux-amd64/go1.16".into()) => "Juice/v1.1.0/linux-amd64/go1.16"
+    );
+
+    rpc_test!(
+        Client:chain_id, String::from("sys") => "juice_chainId", vec![r#""sys""#];
+        Value::String("0x3113a".into()) => 0x3113a
+    );
+
+    rpc_test!(
+        Client:protocol_version, String::from("sys") => "juice_protocolVersion", vec![r#""sys""#];
+        Value::String("0x41".into()) => 0x41
+    );
+
+    rpc_test!(
+        Client:transaction_count, String::from("sys"), H256::from_low_u64_be(0x123)
+            => "juice_getBlockTransactionCountByHash", vec![r#""sys""#, r#""0x0000000000000000000000000000000000000000000000000000000000000123""#];
+        Value::String("0x3".into()) => U64::from(3)
+    );
+
+    rpc_test!(
+        Client:transaction_count_by_number, String::from("sys"), BlockNumber::Number(0x10.into())
+            => "juice_getBlockTransactionCountByNumber", vec![r#""sys""#, r#""0x10""#];
+        Value::String("0x3".into()) => U64::from(3)
+    );
+
+    rpc_test!(
+        Client:transaction_in_block_by_number, String::from("sys"), BlockNumber::Latest, 0x1
+            => "juice_getTransactionByBlockNumberAndIndex", vec![r#""sys""#, r#""latest""#, r#""0x1""#];
+        Value::Null => None
+    );
+
     rpc_test!(
         Client:block_number, String::from("sys") => "juice_blockNumber", vec![r#""sys""#];
         Value::String("0x123".into()) => 0x123
@@ -381,6 +585,33 @@ mod tests {
         Value::String("0x0000000000000000000000000000000000000000000000000000000000000123".into()) => H256::from_low_u64_be(0x123).to_fixed_bytes()
     );
 
+    rpc_test!(
+        Client:proof, String::from("sys"), Address::from("lax18qg084alcnuv2jjdx4u68vw4ve8lffm0ncrzww"), vec![H256::from_low_u64_be(0x1)], Some(BlockNumber::Number(0x10.into()))
+            => "juice_getProof", vec![
+                r#""sys""#,
+                r#""lax18qg084alcnuv2jjdx4u68vw4ve8lffm0ncrzww""#,
+                r#"["0x0000000000000000000000000000000000000000000000000000000000000001"]"#,
+                r#""0x10""#
+            ];
+        ::serde_json::json!({
+            "address": "lax18qg084alcnuv2jjdx4u68vw4ve8lffm0ncrzww",
+            "balance": "0x0",
+            "codeHash": H256::zero(),
+            "nonce": "0x0",
+            "storageHash": crate::trie::EMPTY_ROOT,
+            "accountProof": [],
+            "storageProof": []
+        }) => crate::types::Proof {
+            address: "lax18qg084alcnuv2jjdx4u68vw4ve8lffm0ncrzww".into(),
+            balance: 0.into(),
+            code_hash: H256::zero(),
+            nonce: 0.into(),
+            storage_hash: crate::trie::EMPTY_ROOT,
+            account_proof: vec![],
+            storage_proof: vec![],
+        }
+    );
+
     rpc_test!(
         Client:code_at, String::from("sys"), Address::from("lax18qg084alcnuv2jjdx4u68vw4ve8lffm0ncrzww"),  None
             => "juice_getCode", vec![
@@ -393,9 +624,9 @@ mod tests {
 
     rpc_test!(
         Client:block_by_number, String::from("sys"), BlockNumber::Pending
-            => "juice_getBlockByNumber", vec![r#""sys""#, r#""pending""#, r#"true"#];
+            => "juice_getBlockByNumber", vec![r#""sys""#, r#""pending""#, r#"false"#];
         ::serde_json::from_str(EXAMPLE_BLOCK).unwrap()
-            => Some(::serde_json::from_str::<Block<Transaction>>(EXAMPLE_BLOCK).unwrap())
+            => Some(::serde_json::from_str::<Block<H256>>(EXAMPLE_BLOCK).unwrap())
     );
 
     rpc_test!(
@@ -404,4 +635,96 @@ mod tests {
         ::serde_json::from_str(EXAMPLE_BLOCK).unwrap()
             => Some(::serde_json::from_str::<Block<Transaction>>(EXAMPLE_BLOCK).unwrap())
     );
+
+    #[test]
+    fn should_default_missing_logs_bloom() {
+        use crate::types::BlockHeader;
+
+        let mut json = ::serde_json::from_str::<Value>(EXAMPLE_BLOCK).unwrap();
+        json.as_object_mut().unwrap().remove("logsBloom");
+
+        let header = ::serde_json::from_value::<BlockHeader>(json.clone()).unwrap();
+        let block = ::serde_json::from_value::<Block<H256>>(json).unwrap();
+
+        assert!(header.logs_bloom.is_zero());
+        assert!(block.logs_bloom.is_zero());
+    }
+
+    #[test]
+    fn should_verify_header_hash() {
+        use crate::types::BlockHeader;
+
+        // given
+        let mut header = ::serde_json::from_str::<BlockHeader>(EXAMPLE_BLOCK).unwrap();
+        let block = ::serde_json::from_str::<Block<H256>>(EXAMPLE_BLOCK).unwrap();
+
+        // then
+        assert_eq!(block.header(), header);
+        assert_eq!(header.verify_hash(), Ok(true));
+        header.gas_used = 1.into();
+        assert_eq!(header.verify_hash(), Ok(false));
+    }
+
+    #[test]
+    fn should_verify_empty_block_roots() {
+        let block = ::serde_json::from_str::<Block<Transaction>>(EXAMPLE_BLOCK).unwrap();
+
+        assert_eq!(block.transaction_root, crate::trie::EMPTY_ROOT);
+        assert_eq!(block.verify_transactions(), Ok(true));
+        assert_eq!(block.verify_receipts(&[]), Ok(true));
+    }
+
+    #[test]
+    fn should_replay_failed_transaction() {
+        use crate::{transports::MockTransport, types::RevertReason};
+        use serde_json::json;
+
+        // given
+        let tx_hash = "0x0000000000000000000000000000000000000000000000000000000000000123";
+        let mock = MockTransport::new();
+        mock.expect("juice_getTransactionByHash").returns(json!({
+            "hash": tx_hash,
+            "nonce": "0x1",
+            "blockHash": "0x0000000000000000000000000000000000000000000000000000000000000456",
+            "blockNumber": "0x10",
+            "transactionIndex": "0x0",
+            "from": "lax18qg084alcnuv2jjdx4u68vw4ve8lffm0ncrzww",
+            "to": "lax18qg084alcnuv2jjdx4u68vw4ve8lffm0ncrzww",
+            "value": "0x0",
+            "gasPrice": "0x1",
+            "gas": "0x5208",
+            "input": "0x12345678"
+        }));
+        mock.expect("juice_call").returns_error(crate::rpc::Error {
+            code: crate::rpc::ErrorCode::ServerError(3),
+            message: "execution reverted: not owner".into(),
+            data: Some(json!("0x08c379a0000000000000000000000000000000000000000000000000000000000000002000000000000000000000000000000000000000000000000000000000000000096e6f74206f776e65720000000000000000000000000000000000000000000000")),
+        });
+        let receipt: crate::types::TransactionReceipt = serde_json::from_value(json!({
+            "transactionHash": tx_hash,
+            "transactionIndex": "0x0",
+            "blockHash": null,
+            "blockNumber": "0x10",
+            "cumulativeGasUsed": "0x5208",
+            "gasUsed": "0x5208",
+            "contractAddress": null,
+            "logs": [],
+            "status": "0x0",
+            "root": null,
+            "logsBloom": format!("0x{}", "0".repeat(512))
+        }))
+        .unwrap();
+        let client = super::Client::new(mock.clone(), true);
+
+        // when
+        let reason =
+            futures::executor::block_on(client.ledger("sys").revert_reason(&receipt)).unwrap();
+
+        // then
+        assert_eq!(reason, Some(RevertReason::Error("not owner".into())));
+        let calls = mock.calls();
+        assert_eq!(calls[1].0, "juice_call");
+        assert_eq!(calls[1].1[2], json!("0xf"));
+        assert_eq!(calls[1].1[1]["data"], json!("0x12345678"));
+    }
 }
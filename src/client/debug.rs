@@ -0,0 +1,140 @@
+use crate::{
+    helpers::{self, CallFuture},
+    types::{BadBlock, CallFrame, H256},
+    Transport,
+};
+use serde_json::json;
+
+/// `debug` namespace of the node, to trace transactions and inspect
+/// rejected blocks.
+#[derive(Debug, Clone)]
+pub struct Debug<T: Transport> {
+    transport: T,
+}
+
+impl<T: Transport> Debug<T> {
+    /// Create a new `debug` namespace client.
+    pub fn new(transport: T) -> Self {
+        Self { transport }
+    }
+
+    /// Returns the underlying transport.
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    /// Replays the transaction with the `callTracer`, returns its call tree.
+    pub fn trace_transaction(&self, ledger: String, hash: H256) -> CallFuture<CallFrame, T::Out> {
+        let ledger = helpers::serialize(&ledger);
+        let hash = helpers::serialize(&hash);
+        let options = json!({ "tracer": "callTracer" });
+
+        CallFuture::new(
+            self.transport
+                .execute("debug_traceTransaction", vec![ledger, hash, options]),
+        )
+    }
+
+    /// Returns the last blocks rejected by the node.
+    pub fn bad_blocks(&self, ledger: String) -> CallFuture<Vec<BadBlock>, T::Out> {
+        let ledger = helpers::serialize(&ledger);
+        CallFuture::new(self.transport.execute("debug_getBadBlocks", vec![ledger]))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::tests::TestTransport;
+
+    #[test]
+    fn should_trace_transaction() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.set_response(json!({
+            "type": "CALL",
+            "from": "lax18qg084alcnuv2jjdx4u68vw4ve8lffm0ncrzww",
+            "to": "lax1token",
+            "value": "0x0",
+            "gas": "0x7530",
+            "gasUsed": "0x6d60",
+            "input": "0xa9059cbb",
+            "error": "execution reverted",
+            "calls": [{
+                "type": "STATICCALL",
+                "from": "lax1token",
+                "to": "lax1oracle",
+                "gas": "0x1000",
+                "gasUsed": "0x100",
+                "input": "0x",
+                "output": "0x01"
+            }, {
+                "type": "DELEGATECALL",
+                "from": "lax1token",
+                "to": "lax1logic",
+                "gas": "0x1000",
+                "gasUsed": "0x1000",
+                "input": "0x",
+                "error": "out of gas"
+            }]
+        }));
+        let debug = Debug::new(&transport);
+
+        // when
+        let trace = debug.trace_transaction("sys".into(), H256::from_low_u64_be(0x123));
+        let trace = futures::executor::block_on(trace).unwrap();
+
+        // then
+        transport.assert_request(
+            "debug_traceTransaction",
+            &[
+                r#""sys""#.into(),
+                r#""0x0000000000000000000000000000000000000000000000000000000000000123""#.into(),
+                r#"{"tracer":"callTracer"}"#.into(),
+            ],
+        );
+        transport.assert_no_more_requests();
+        assert_eq!(trace.calls.len(), 2);
+        assert_eq!(trace.calls[0].output, Some(vec![1].into()));
+        let failed = trace.failed_calls();
+        assert_eq!(failed.len(), 2);
+        assert_eq!(failed[1].error.as_deref(), Some("out of gas"));
+    }
+
+    #[test]
+    fn should_get_bad_blocks() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.set_response(json!([{
+            "hash": H256::repeat_byte(0xbb),
+            "block": {
+                "parentHash": H256::repeat_byte(0xaa),
+                "miner": "lax18qg084alcnuv2jjdx4u68vw4ve8lffm0ncrzww",
+                "stateRoot": H256::zero(),
+                "transactionsRoot": crate::trie::EMPTY_ROOT,
+                "receiptsRoot": crate::trie::EMPTY_ROOT,
+                "number": "0x10",
+                "gasLimit": "0x111",
+                "gasUsed": "0x0",
+                "timestamp": "0x1111",
+                "extraData": "0x",
+                "nonce": "0x",
+                "hash": H256::repeat_byte(0xbb),
+                "transactions": []
+            },
+            "rlp": "0xc0"
+        }]));
+        let debug = Debug::new(&transport);
+
+        // when
+        let bad = futures::executor::block_on(debug.bad_blocks("sys".into())).unwrap();
+
+        // then
+        transport.assert_request("debug_getBadBlocks", &[r#""sys""#.into()]);
+        transport.assert_no_more_requests();
+        assert_eq!(bad.len(), 1);
+        assert_eq!(bad[0].block.number, Some(0x10.into()));
+        assert!(bad[0].block.logs_bloom.is_zero());
+        assert_eq!(bad[0].rlp.0, vec![0xc0]);
+    }
+}
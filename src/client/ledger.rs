@@ -0,0 +1,238 @@
+use crate::{
+    client::Client,
+    error::Result,
+    helpers::CallFuture,
+    types::{
+        Address, Block, BlockHeader, BlockNumber, Bytes, CallRequest, Index, Proof, RevertReason,
+        SyncState, Transaction, TransactionReceipt, TransactionRequest, H256, U256, U64,
+    },
+    Transport,
+};
+
+/// Client bound to a single ledger.
+///
+/// Exposes the same methods as `Client` without the `ledger` argument.
+#[derive(Debug, Clone)]
+pub struct LedgerClient<T: Transport> {
+    client: Client<T>,
+    ledger: String,
+}
+
+impl<T: Transport> LedgerClient<T> {
+    /// Create a new client bound to the given ledger.
+    pub fn new(client: Client<T>, ledger: String) -> Self {
+        Self { client, ledger }
+    }
+
+    /// Returns the name of the ledger.
+    pub fn name(&self) -> &str {
+        &self.ledger
+    }
+
+    /// Returns the client which is not bound to any ledger.
+    pub fn client(&self) -> &Client<T> {
+        &self.client
+    }
+
+    /// Get the current block number from chain.
+    pub fn block_number(&self) -> CallFuture<U64, T::Out> {
+        self.client.block_number(self.ledger.clone())
+    }
+
+    /// Returns the block details with the given hash.
+    pub fn block_by_hash(&self, hash: H256) -> CallFuture<Option<Block<Transaction>>, T::Out> {
+        self.client.block_by_hash(self.ledger.clone(), hash)
+    }
+
+    /// Returns the block full details with the given number.
+    pub fn block_by_number_txs(
+        &self,
+        number: BlockNumber,
+    ) -> CallFuture<Option<Block<Transaction>>, T::Out> {
+        self.client.block_by_number_txs(self.ledger.clone(), number)
+    }
+
+    /// Returns the block details with the given number.
+    pub fn block_by_number(&self, number: BlockNumber) -> CallFuture<Option<Block<H256>>, T::Out> {
+        self.client.block_by_number(self.ledger.clone(), number)
+    }
+
+    /// Returns the block header with the given hash.
+    pub fn header_by_hash(&self, hash: H256) -> CallFuture<Option<BlockHeader>, T::Out> {
+        self.client.header_by_hash(self.ledger.clone(), hash)
+    }
+
+    /// Returns a block header from the current canonical chain.
+    pub fn header_by_number(&self, number: BlockNumber) -> CallFuture<Option<BlockHeader>, T::Out> {
+        self.client.header_by_number(self.ledger.clone(), number)
+    }
+
+    /// Returns the transation with the given hash.
+    pub fn transaction_by_hash(&self, hash: H256) -> CallFuture<Option<Transaction>, T::Out> {
+        self.client.transaction_by_hash(self.ledger.clone(), hash)
+    }
+
+    /// Returns the total number of transactions in the given block.
+    pub fn transaction_count(&self, block_hash: H256) -> CallFuture<U64, T::Out> {
+        self.client
+            .transaction_count(self.ledger.clone(), block_hash)
+    }
+
+    /// Returns a single transaction at index in the given block.
+    pub fn transaction_in_block(
+        &self,
+        block_hash: H256,
+        index: Index,
+    ) -> CallFuture<Option<Transaction>, T::Out> {
+        self.client
+            .transaction_in_block(self.ledger.clone(), block_hash, index)
+    }
+
+    /// Returns the receipt of a transaction by transaction hash.
+    pub fn transaction_receipt(
+        &self,
+        tx_hash: H256,
+    ) -> CallFuture<Option<TransactionReceipt>, T::Out> {
+        self.client
+            .transaction_receipt(self.ledger.clone(), tx_hash)
+    }
+
+    /// Returns the chain id used to sign the transactions of the ledger.
+    pub fn chain_id(&self) -> CallFuture<U64, T::Out> {
+        self.client.chain_id(self.ledger.clone())
+    }
+
+    /// Returns the version of the protocol spoken by the node.
+    pub fn protocol_version(&self) -> CallFuture<U64, T::Out> {
+        self.client.protocol_version(self.ledger.clone())
+    }
+
+    /// Returns the total number of transactions in the block with the given
+    /// number.
+    pub fn transaction_count_by_number(&self, number: BlockNumber) -> CallFuture<U64, T::Out> {
+        self.client
+            .transaction_count_by_number(self.ledger.clone(), number)
+    }
+
+    /// Returns a single transaction at index in the block with the given
+    /// number.
+    pub fn transaction_in_block_by_number(
+        &self,
+        number: BlockNumber,
+        index: Index,
+    ) -> CallFuture<Option<Transaction>, T::Out> {
+        self.client
+            .transaction_in_block_by_number(self.ledger.clone(), number, index)
+    }
+
+    /// Retrieves the current progress of the sync algorithm.
+    pub fn sync_progress(&self) -> CallFuture<SyncState, T::Out> {
+        self.client.sync_progress(self.ledger.clone())
+    }
+
+    /// Returns the VON balance of the given account.
+    pub fn balance_at(
+        &self,
+        account: Address,
+        block: Option<BlockNumber>,
+    ) -> CallFuture<U256, T::Out> {
+        self.client.balance_at(self.ledger.clone(), account, block)
+    }
+
+    /// Returns the value of key in the contract storage of the given account.
+    pub fn storage_at(
+        &self,
+        account: Address,
+        key: H256,
+        block: Option<BlockNumber>,
+    ) -> CallFuture<Bytes, T::Out> {
+        self.client
+            .storage_at(self.ledger.clone(), account, key, block)
+    }
+
+    /// Returns the account and storage proofs of the given account.
+    pub fn proof(
+        &self,
+        account: Address,
+        keys: Vec<H256>,
+        block: Option<BlockNumber>,
+    ) -> CallFuture<Proof, T::Out> {
+        self.client.proof(self.ledger.clone(), account, keys, block)
+    }
+
+    /// Returns the contract code of the given account.
+    pub fn code_at(
+        &self,
+        account: Address,
+        block: Option<BlockNumber>,
+    ) -> CallFuture<Bytes, T::Out> {
+        self.client.code_at(self.ledger.clone(), account, block)
+    }
+
+    /// Returns the account nonce of the given account.
+    pub fn nonce_at(
+        &self,
+        account: Address,
+        block: Option<BlockNumber>,
+    ) -> CallFuture<U64, T::Out> {
+        self.client.nonce_at(self.ledger.clone(), account, block)
+    }
+
+    /// Contract calling
+    pub fn call(&self, req: CallRequest, number: BlockNumber) -> CallFuture<Bytes, T::Out> {
+        self.client.call(self.ledger.clone(), req, number)
+    }
+
+    /// Retrieves the currently suggested gas price.
+    pub fn suggest_gas_price(&self) -> CallFuture<U256, T::Out> {
+        self.client.suggest_gas_price(self.ledger.clone())
+    }
+
+    /// Tries to estimate the gas needed to execute a specific transaction.
+    pub fn estimate_gas(&self, req: CallRequest) -> CallFuture<U64, T::Out> {
+        self.client.estimate_gas(self.ledger.clone(), req)
+    }
+
+    /// Injects a signed transaction into the pending pool for execution.
+    pub fn send_transaction(&self, tx: TransactionRequest) -> CallFuture<H256, T::Out> {
+        self.client.send_transaction(self.ledger.clone(), tx)
+    }
+
+    /// Injects a signed transaction into the pending pool for execution.
+    pub fn send_raw_transaction(&self, rlp: Bytes) -> CallFuture<H256, T::Out> {
+        self.client.send_raw_transaction(self.ledger.clone(), rlp)
+    }
+
+    /// Recovers the revert reason of a failed transaction.
+    pub async fn revert_reason(
+        &self,
+        receipt: &TransactionReceipt,
+    ) -> Result<Option<RevertReason>> {
+        self.client
+            .revert_reason(self.ledger.clone(), receipt)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{client::Client, helpers::tests::TestTransport, rpc::Value, types::U64};
+
+    #[test]
+    fn should_pass_bound_ledger() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.set_response(Value::String("0x123".into()));
+        let client = Client::new(&transport, true);
+
+        // when
+        let ledger = client.ledger("test");
+        let result = futures::executor::block_on(ledger.block_number());
+
+        // then
+        assert_eq!(ledger.name(), "test");
+        transport.assert_request("juice_blockNumber", &[r#""test""#.into()]);
+        transport.assert_no_more_requests();
+        assert_eq!(result, Ok(U64::from(0x123)));
+    }
+}
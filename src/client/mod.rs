@@ -1,2 +1,13 @@
+pub mod admin;
+#[allow(clippy::module_inception)]
 pub mod client;
+pub mod debug;
+pub mod ledger;
+mod multi_ledger;
+pub mod paginator;
+
+pub use admin::Admin;
 pub use client::Client;
+pub use debug::Debug;
+pub use ledger::LedgerClient;
+pub use paginator::Paginator;
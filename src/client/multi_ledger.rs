@@ -0,0 +1,166 @@
+use crate::{
+    client::{Client, LedgerClient, Paginator},
+    error::Result,
+    types::LedgerPage,
+    Transport,
+};
+use futures::{future, Future, FutureExt, TryStreamExt};
+use std::collections::{HashMap, HashSet};
+
+impl<T: Transport> Client<T> {
+    /// Runs `query` concurrently against each of the given ledgers.
+    ///
+    /// Errors are kept per ledger, so one failing ledger does not hide the
+    /// results of the others. A ledger named several times is queried once.
+    ///
+    /// ```no_run
+    /// # async fn run(client: juice_sdk_rs::client::Client<juice_sdk_rs::transports::Http>) {
+    /// let numbers = client
+    ///     .for_each_ledger(vec!["sys", "test"], |ledger| ledger.block_number())
+    ///     .await;
+    /// # }
+    /// ```
+    pub fn for_each_ledger<I, S, F, Fut, R>(
+        &self,
+        ledgers: I,
+        query: F,
+    ) -> impl Future<Output = HashMap<String, Result<R>>>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+        F: Fn(LedgerClient<T>) -> Fut,
+        Fut: Future<Output = Result<R>>,
+    {
+        let mut seen = HashSet::new();
+        let queries = ledgers
+            .into_iter()
+            .map(Into::into)
+            .filter(|ledger: &String| seen.insert(ledger.clone()))
+            .map(|ledger| {
+                let ledger = self.ledger(ledger);
+                let name = ledger.name().to_owned();
+                query(ledger).map(move |result| (name, result))
+            })
+            .collect::<Vec<_>>();
+
+        future::join_all(queries).map(|results| results.into_iter().collect())
+    }
+
+    /// Runs `query` concurrently against every ledger registered in the
+    /// ledger system contract.
+    ///
+    /// `ledger_pages` is the paginated query of the ledger system contract,
+    /// called with the page number and size. Every page is fetched through a
+    /// `Paginator` before the ledgers are queried. Fails only if the ledgers
+    /// can't be discovered.
+    pub async fn multi_ledger<P, PFut, F, Fut, R>(
+        &self,
+        ledger_pages: P,
+        query: F,
+    ) -> Result<HashMap<String, Result<R>>>
+    where
+        P: FnMut(u32, u32) -> PFut,
+        PFut: Future<Output = Result<LedgerPage>>,
+        F: Fn(LedgerClient<T>) -> Fut,
+        Fut: Future<Output = Result<R>>,
+    {
+        let ledgers = Paginator::new(ledger_pages)
+            .into_stream()
+            .map_ok(|ledger| ledger.name)
+            .try_collect::<Vec<_>>()
+            .await?;
+        Ok(self.for_each_ledger(ledgers, query).await)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        client::Client,
+        error::Error,
+        helpers::tests::TestTransport,
+        rpc::Value,
+        types::{Ledger, LedgerStatus, Page, U64},
+    };
+    use futures::{executor::block_on, future};
+    use std::cell::RefCell;
+
+    #[test]
+    fn should_query_given_ledgers() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.add_response(Value::String("0x1".into()));
+        transport.add_response(Value::String("0x2".into()));
+        let client = Client::new(&transport, true);
+
+        // when
+        let result =
+            block_on(client.for_each_ledger(vec!["sys", "test"], |ledger| ledger.block_number()));
+
+        // then
+        transport.assert_request("juice_blockNumber", &[r#""sys""#.into()]);
+        transport.assert_request("juice_blockNumber", &[r#""test""#.into()]);
+        transport.assert_no_more_requests();
+        assert_eq!(result.len(), 2);
+        assert_eq!(result["sys"], Ok(U64::from(1)));
+        assert_eq!(result["test"], Ok(U64::from(2)));
+    }
+
+    fn ledger(name: &str) -> Ledger {
+        Ledger {
+            name: name.into(),
+            id: 1,
+            timestamp: 0,
+            consensus: vec![],
+            observe: vec![],
+            status: LedgerStatus::Active,
+        }
+    }
+
+    #[test]
+    fn should_query_discovered_ledgers() {
+        // given
+        let mut transport = TestTransport::default();
+        transport.add_response(Value::String("0x1".into()));
+        let client = Client::new(&transport, true);
+        let requested = RefCell::new(vec![]);
+        let pages = |num, size| {
+            requested.borrow_mut().push(num);
+            let items = match num {
+                1 => vec![ledger("sys")],
+                _ => vec![ledger("test"), ledger("sys")],
+            };
+            future::ready(Ok(Page {
+                total_count: 3,
+                total_page: 2,
+                page_num: num,
+                page_size: size,
+                items,
+            }))
+        };
+
+        // when
+        let result = block_on(client.multi_ledger(pages, |ledger| ledger.block_number())).unwrap();
+
+        // then
+        assert_eq!(*requested.borrow(), vec![1, 2]);
+        transport.assert_request("juice_blockNumber", &[r#""sys""#.into()]);
+        transport.assert_request("juice_blockNumber", &[r#""test""#.into()]);
+        transport.assert_no_more_requests();
+        assert_eq!(result.len(), 2);
+        assert_eq!(result["sys"], Ok(U64::from(1)));
+        assert_eq!(result["test"], Err(Error::Unreachable));
+    }
+
+    #[test]
+    fn should_fail_if_ledgers_are_not_discovered() {
+        let transport = TestTransport::default();
+        let client = Client::new(&transport, true);
+        let pages = |_, _| future::ready(Err::<Page<Ledger>, _>(Error::Unreachable));
+
+        let result = block_on(client.multi_ledger(pages, |ledger| ledger.block_number()));
+
+        assert_eq!(result, Err(Error::Unreachable));
+        transport.assert_no_more_requests();
+    }
+}
@@ -0,0 +1,147 @@
+use crate::{error::Result, types::Page};
+use futures::{
+    stream::{self, Stream, StreamExt},
+    Future,
+};
+
+/// Default number of items requested per page.
+pub const DEFAULT_PAGE_SIZE: u32 = 20;
+/// Default number of pages fetched concurrently.
+pub const DEFAULT_CONCURRENCY: usize = 4;
+
+/// Turns a paginated system contract query into a stream of items.
+///
+/// `fetch` is called with the page number (starting from 1) and the page
+/// size. The first page is fetched alone to learn the total number of pages,
+/// the remaining ones are prefetched with at most `concurrency` requests in
+/// flight, but items are always yielded in order.
+#[derive(Debug, Clone)]
+pub struct Paginator<F> {
+    fetch: F,
+    page_size: u32,
+    concurrency: usize,
+}
+
+impl<F, Fut, T> Paginator<F>
+where
+    F: FnMut(u32, u32) -> Fut,
+    Fut: Future<Output = Result<Page<T>>>,
+{
+    /// Create a new paginator over the given page query.
+    pub fn new(fetch: F) -> Self {
+        Self {
+            fetch,
+            page_size: DEFAULT_PAGE_SIZE,
+            concurrency: DEFAULT_CONCURRENCY,
+        }
+    }
+
+    /// Set the number of items requested per page.
+    pub fn page_size(mut self, page_size: u32) -> Self {
+        self.page_size = page_size.max(1);
+        self
+    }
+
+    /// Set the maximum number of pages fetched concurrently.
+    pub fn concurrency(mut self, concurrency: usize) -> Self {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Returns a stream of all items, across all pages.
+    ///
+    /// A failing page yields its error in place of its items; the stream
+    /// stops after an error of the first page.
+    pub fn into_stream(self) -> impl Stream<Item = Result<T>> {
+        let Paginator {
+            mut fetch,
+            page_size,
+            concurrency,
+        } = self;
+
+        stream::once(async move {
+            let first = fetch(1, page_size).await;
+            let rest = match first {
+                Ok(ref page) => {
+                    let pages = page.page_num.max(1) + 1..=page.total_page;
+                    let pages = stream::iter(pages)
+                        .map(move |num| fetch(num, page_size))
+                        .buffered(concurrency);
+                    Some(pages)
+                }
+                Err(_) => None,
+            };
+            stream::once(async { first })
+                .chain(stream::iter(rest).flatten())
+                .flat_map(|page| stream::iter(page_items(page)))
+        })
+        .flatten()
+    }
+}
+
+fn page_items<T>(page: Result<Page<T>>) -> Vec<Result<T>> {
+    match page {
+        Ok(page) => page.items.into_iter().map(Ok).collect(),
+        Err(err) => vec![Err(err)],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Paginator;
+    use crate::{error::Error, types::Page};
+    use futures::{executor::block_on, future, StreamExt};
+    use std::cell::RefCell;
+
+    fn page(num: u32, size: u32, total: u32) -> Page<u32> {
+        let start = (num - 1) * size;
+        let end = (start + size).min(total);
+        Page {
+            total_count: total,
+            total_page: total.div_ceil(size),
+            page_num: num,
+            page_size: size,
+            items: (start..end).collect(),
+        }
+    }
+
+    #[test]
+    fn should_stream_all_items_in_order() {
+        let requested = RefCell::new(vec![]);
+        let items = Paginator::new(|num, size| {
+            requested.borrow_mut().push((num, size));
+            future::ready(Ok(page(num, size, 7)))
+        })
+        .page_size(3)
+        .concurrency(2)
+        .into_stream()
+        .collect::<Vec<_>>();
+        let items = block_on(items);
+
+        assert_eq!(
+            items.into_iter().map(Result::unwrap).collect::<Vec<_>>(),
+            (0..7).collect::<Vec<_>>()
+        );
+        assert_eq!(*requested.borrow(), vec![(1, 3), (2, 3), (3, 3)]);
+    }
+
+    #[test]
+    fn should_yield_page_errors() {
+        let items = Paginator::new(|num, size| {
+            future::ready(if num == 2 {
+                Err(Error::Unreachable)
+            } else {
+                Ok(page(num, size, 6))
+            })
+        })
+        .page_size(2)
+        .into_stream()
+        .collect::<Vec<_>>();
+        let items = block_on(items);
+
+        assert_eq!(
+            items,
+            vec![Ok(0), Ok(1), Err(Error::Unreachable), Ok(4), Ok(5)]
+        );
+    }
+}
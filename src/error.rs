@@ -1,7 +1,10 @@
-use crate::rpc::error::Error as RPCError;
+use crate::{
+    rpc::{error::Error as RPCError, ErrorCode, Value},
+    types::{Bytes, RevertReason},
+};
 use derive_more::{Display, From};
 use serde_json::Error as SerdeError;
-use std::io::Error as IoError;
+use std::{io::Error as IoError, time::Duration};
 
 pub type Result<T = ()> = std::result::Result<T, Error>;
 
@@ -18,27 +21,263 @@ pub enum Error {
     #[display(fmt = "Got invalid response: {}", _0)]
     #[from(ignore)]
     InvalidResponse(String),
+    /// response failed verification
+    #[display(fmt = "Verification failed: {}", _0)]
+    #[from(ignore)]
+    Verification(String),
     /// transport error
     #[display(fmt = "Transport error: {}", _0)]
     #[from(ignore)]
     Transport(String),
+    /// request timed out
+    #[display(fmt = "Request timed out")]
+    Timeout,
+    /// connection to the node failed, the request was not sent
+    #[display(fmt = "Connection error: {}", _0)]
+    #[from(ignore)]
+    Connection(String),
+    /// http error status
+    #[display(fmt = "HTTP status: {}", status)]
+    #[from(ignore)]
+    HttpStatus {
+        /// Status code of the response
+        status: u16,
+        /// Delay requested by the `Retry-After` header
+        retry_after: Option<Duration>,
+    },
     /// rpc error
     #[display(fmt = "RPC error: {}", _0)]
+    #[from(ignore)]
     Rpc(RPCError),
+    /// rpc error recognized as a common node failure
+    #[display(fmt = "Node error: {}", error)]
+    #[from(ignore)]
+    Node {
+        /// Recognized failure
+        kind: NodeError,
+        /// Error returned by the node
+        error: RPCError,
+    },
     /// io error
     #[display(fmt = "Recovery error: {}", _0)]
     Io(IoError),
     /// internal error
     #[display(fmt = "Internal error")]
     Internal,
+    /// not enough nodes returned the same response
+    #[display(fmt = "Quorum not reached: {} matching responses required", required)]
+    #[from(ignore)]
+    QuorumNotReached {
+        /// Number of matching responses required
+        required: usize,
+        /// Response of every queried node, by index of the node
+        responses: Vec<(usize, Result<Value>)>,
+    },
+}
+
+/// Common node failures, recognized from the code and message of the RPC
+/// error.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NodeError {
+    /// The transaction nonce is lower than the account nonce.
+    NonceTooLow,
+    /// The account can't pay for the gas and the transfered value.
+    InsufficientBalance,
+    /// The transaction gas exceeds the block gas limit, or the execution ran
+    /// out of gas.
+    GasLimitExceeded,
+    /// The execution was reverted.
+    ExecutionReverted {
+        /// Revert data returned by the contract
+        data: Option<Bytes>,
+    },
+    /// The ledger does not exist.
+    UnknownLedger,
+    /// The account is not allowed to perform the operation.
+    PermissionDenied,
+    /// The transaction is already in the pool.
+    AlreadyKnown,
+}
+
+/// Code of the `execution reverted` errors.
+const EXECUTION_REVERTED_CODE: i64 = 3;
+
+impl NodeError {
+    /// Recognizes the failure of the given RPC error, if any.
+    pub fn from_rpc(error: &RPCError) -> Option<Self> {
+        let message = error.message.to_lowercase();
+        let contains = |patterns: &[&str]| patterns.iter().any(|p| message.contains(p));
+
+        if error.code == ErrorCode::ServerError(EXECUTION_REVERTED_CODE)
+            || message.starts_with("execution reverted")
+        {
+            let data = error
+                .data
+                .clone()
+                .and_then(|data| serde_json::from_value(data).ok());
+            return Some(NodeError::ExecutionReverted { data });
+        }
+        let kind = if contains(&["nonce too low"]) {
+            NodeError::NonceTooLow
+        } else if contains(&["insufficient funds", "insufficient balance"]) {
+            NodeError::InsufficientBalance
+        } else if contains(&[
+            "exceeds block gas limit",
+            "gas limit exceeded",
+            "out of gas",
+        ]) {
+            NodeError::GasLimitExceeded
+        } else if contains(&["unknown ledger", "ledger not found", "ledger not exist"]) {
+            NodeError::UnknownLedger
+        } else if contains(&["permission denied", "no permission", "not authorized"]) {
+            NodeError::PermissionDenied
+        } else if contains(&["already known", "known transaction"]) {
+            NodeError::AlreadyKnown
+        } else {
+            return None;
+        };
+        Some(kind)
+    }
+
+    /// Returns the name of the failure, for metrics and logs.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            NodeError::NonceTooLow => "nonce_too_low",
+            NodeError::InsufficientBalance => "insufficient_balance",
+            NodeError::GasLimitExceeded => "gas_limit_exceeded",
+            NodeError::ExecutionReverted { .. } => "execution_reverted",
+            NodeError::UnknownLedger => "unknown_ledger",
+            NodeError::PermissionDenied => "permission_denied",
+            NodeError::AlreadyKnown => "already_known",
+        }
+    }
+}
+
+impl From<RPCError> for Error {
+    fn from(error: RPCError) -> Self {
+        match NodeError::from_rpc(&error) {
+            Some(kind) => Error::Node { kind, error },
+            None => Error::Rpc(error),
+        }
+    }
+}
+
+impl Error {
+    /// Returns true if the error is caused by the transport, not by the node.
+    pub fn is_transport(&self) -> bool {
+        matches!(
+            self,
+            Error::Unreachable
+                | Error::Transport(_)
+                | Error::Timeout
+                | Error::Connection(_)
+                | Error::HttpStatus { .. }
+                | Error::Io(_)
+        )
+    }
+
+    /// Returns true if sending the same request again may succeed.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Error::HttpStatus { status, .. } => *status == 408 || *status == 429 || *status >= 500,
+            err => err.is_transport(),
+        }
+    }
+
+    /// Returns the recognized node failure, if any.
+    pub fn node_error(&self) -> Option<&NodeError> {
+        match self {
+            Error::Node { kind, .. } => Some(kind),
+            _ => None,
+        }
+    }
+
+    /// Returns the reason of a reverted execution, if the error is one.
+    pub fn revert_reason(&self) -> Option<RevertReason> {
+        self.decode_revert(RevertReason::decode)
+    }
+
+    /// Returns the reason of a reverted execution, decoding the custom
+    /// errors of the given contract ABI.
+    pub fn revert_reason_with_abi(&self, abi: &ethabi::Contract) -> Option<RevertReason> {
+        self.decode_revert(|data| RevertReason::decode_with_abi(data, abi))
+    }
+
+    fn decode_revert(&self, decode: impl FnOnce(&[u8]) -> RevertReason) -> Option<RevertReason> {
+        let (data, error) = match self {
+            Error::Node {
+                kind: NodeError::ExecutionReverted { data },
+                error,
+            } => (data, error),
+            _ => return None,
+        };
+        match data {
+            Some(data) if !data.0.is_empty() => Some(decode(&data.0)),
+            // Some nodes only return the reason in the message.
+            _ => {
+                let reason = error
+                    .message
+                    .split_once(": ")
+                    .map(|(_, reason)| reason.to_owned());
+                Some(match reason {
+                    Some(reason) => RevertReason::Error(reason),
+                    None => RevertReason::Unknown(Bytes::default()),
+                })
+            }
+        }
+    }
+
+    /// Returns the error returned by the node, if any.
+    pub fn rpc_error(&self) -> Option<&RPCError> {
+        match self {
+            Error::Rpc(error) | Error::Node { error, .. } => Some(error),
+            _ => None,
+        }
+    }
+
+    /// Returns the name of the variant, for metrics and logs.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::Unreachable => "unreachable",
+            Error::Decoder(_) => "decoder",
+            Error::InvalidResponse(_) => "invalid_response",
+            Error::Verification(_) => "verification",
+            Error::Transport(_) => "transport",
+            Error::Timeout => "timeout",
+            Error::Connection(_) => "connection",
+            Error::HttpStatus { .. } => "http_status",
+            Error::Rpc(_) => "rpc",
+            Error::Node { kind, .. } => kind.kind(),
+            Error::Io(_) => "io",
+            Error::Internal => "internal",
+            Error::QuorumNotReached { .. } => "quorum_not_reached",
+        }
+    }
+
+    /// Returns the delay requested by the server before sending more requests.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Error::HttpStatus { retry_after, .. } => *retry_after,
+            _ => None,
+        }
+    }
 }
 
 impl std::error::Error for Error {
     fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
         use self::Error::*;
         match self {
-            Unreachable | Decoder(_) | InvalidResponse(_) | Transport(_) | Internal => None,
-            Rpc(ref e) => Some(e),
+            Unreachable
+            | Decoder(_)
+            | InvalidResponse(_)
+            | Verification(_)
+            | Transport(_)
+            | Timeout
+            | Connection(_)
+            | HttpStatus { .. }
+            | Internal
+            | QuorumNotReached { .. } => None,
+            Rpc(ref e) | Node { error: ref e, .. } => Some(e),
             Io(ref e) => Some(e),
         }
     }
@@ -55,13 +294,97 @@ impl PartialEq for Error {
     fn eq(&self, other: &Self) -> bool {
         use self::Error::*;
         match (self, other) {
-            (Unreachable, Unreachable) | (Internal, Internal) => true,
+            (Unreachable, Unreachable) | (Internal, Internal) | (Timeout, Timeout) => true,
             (Decoder(a), Decoder(b))
             | (InvalidResponse(a), InvalidResponse(b))
-            | (Transport(a), Transport(b)) => a == b,
+            | (Verification(a), Verification(b))
+            | (Transport(a), Transport(b))
+            | (Connection(a), Connection(b)) => a == b,
+            (
+                HttpStatus {
+                    status: a,
+                    retry_after: x,
+                },
+                HttpStatus {
+                    status: b,
+                    retry_after: y,
+                },
+            ) => a == b && x == y,
             (Rpc(a), Rpc(b)) => a == b,
+            (Node { error: a, .. }, Node { error: b, .. }) => a == b,
             (Io(a), Io(b)) => a.kind() == b.kind(),
+            (
+                QuorumNotReached {
+                    required: a,
+                    responses: x,
+                },
+                QuorumNotReached {
+                    required: b,
+                    responses: y,
+                },
+            ) => a == b && x == y,
             _ => false,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn rpc_error(code: i64, message: &str) -> RPCError {
+        RPCError {
+            code: ErrorCode::ServerError(code),
+            message: message.into(),
+            data: None,
+        }
+    }
+
+    #[test]
+    fn should_recognize_node_errors() {
+        let kind = |message: &str| NodeError::from_rpc(&rpc_error(-32000, message));
+
+        assert_eq!(kind("nonce too low"), Some(NodeError::NonceTooLow));
+        assert_eq!(
+            kind("insufficient funds for gas * price + value"),
+            Some(NodeError::InsufficientBalance)
+        );
+        assert_eq!(
+            kind("exceeds block gas limit"),
+            Some(NodeError::GasLimitExceeded)
+        );
+        assert_eq!(kind("unknown ledger: l1"), Some(NodeError::UnknownLedger));
+        assert_eq!(kind("Permission denied"), Some(NodeError::PermissionDenied));
+        assert_eq!(kind("already known"), Some(NodeError::AlreadyKnown));
+        assert_eq!(kind("header not found"), None);
+    }
+
+    #[test]
+    fn should_keep_revert_data() {
+        let mut error = rpc_error(3, "execution reverted: not owner");
+        error.data = Some(json!("0x08c379a0"));
+
+        let error = Error::from(error);
+
+        assert_eq!(
+            error.node_error(),
+            Some(&NodeError::ExecutionReverted {
+                data: Some(vec![0x08, 0xc3, 0x79, 0xa0].into())
+            })
+        );
+        assert_eq!(error.kind(), "execution_reverted");
+        assert_eq!(
+            error.rpc_error().map(|e| e.message.as_str()),
+            Some("execution reverted: not owner")
+        );
+    }
+
+    #[test]
+    fn should_keep_unknown_rpc_errors() {
+        let error = Error::from(rpc_error(-32000, "header not found"));
+
+        assert_eq!(error, Error::Rpc(rpc_error(-32000, "header not found")));
+        assert!(!error.is_transport());
+    }
+}
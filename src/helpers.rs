@@ -56,6 +56,41 @@ pub fn to_string<T: serde::Serialize>(request: &T) -> String {
     serde_json::to_string(&request).expect("String serialization never fails.")
 }
 
+/// Compute the Keccak-256 hash of input bytes.
+pub fn keccak256(bytes: &[u8]) -> [u8; 32] {
+    use tiny_keccak::{Hasher, Keccak};
+
+    let mut output = [0u8; 32];
+    let mut hasher = Keccak::v256();
+    hasher.update(bytes);
+    hasher.finalize(&mut output);
+    output
+}
+
+/// Decode a bech32 account address into its 20 bytes.
+pub fn address_to_bytes(address: &str) -> error::Result<ethereum_types::H160> {
+    use bech32::FromBase32;
+
+    let (_, data, _) =
+        bech32::decode(address).map_err(|e| Error::Decoder(format!("{}: {}", address, e)))?;
+    let bytes = Vec::<u8>::from_base32(&data).map_err(|e| Error::Decoder(e.to_string()))?;
+    if bytes.len() != 20 {
+        return Err(Error::Decoder(format!(
+            "{}: invalid address length",
+            address
+        )));
+    }
+    Ok(ethereum_types::H160::from_slice(&bytes))
+}
+
+/// Encode 20 bytes into a bech32 account address with the given prefix.
+pub fn bytes_to_address(hrp: &str, bytes: &ethereum_types::H160) -> String {
+    use bech32::ToBase32;
+
+    bech32::encode(hrp, bytes.as_bytes().to_base32(), bech32::Variant::Bech32)
+        .expect("Address prefix is valid.")
+}
+
 /// Build a JSON-RPC request.
 pub fn build_request(id: usize, method: &str, params: Vec<rpc::Value>) -> rpc::Call {
     rpc::Call::MethodCall(rpc::MethodCall {
@@ -66,6 +101,15 @@ pub fn build_request(id: usize, method: &str, params: Vec<rpc::Value>) -> rpc::C
     })
 }
 
+/// Returns the method name of a JSON-RPC call.
+pub fn call_method(call: &rpc::Call) -> &str {
+    match call {
+        rpc::Call::MethodCall(call) => &call.method,
+        rpc::Call::Notification(notification) => &notification.method,
+        rpc::Call::Invalid { .. } => "",
+    }
+}
+
 /// Parse bytes slice into JSON-RPC response.
 pub fn to_response_from_slice(response: &[u8]) -> error::Result<rpc::Response> {
     arbitrary_precision_deserialize_workaround(response)
@@ -95,7 +139,7 @@ pub fn to_results_from_outputs(
 pub fn to_result_from_output(output: rpc::Output) -> error::Result<rpc::Value> {
     match output {
         rpc::Output::Success(success) => Ok(success.result),
-        rpc::Output::Failure(failure) => Err(error::Error::Rpc(failure.error)),
+        rpc::Output::Failure(failure) => Err(failure.error.into()),
     }
 }
 
@@ -110,13 +154,15 @@ pub mod tests {
     use std::collections::VecDeque;
     use std::marker::Unpin;
     use std::rc::Rc;
+    use std::sync::{Arc, Mutex};
 
     type Result<T> = Box<dyn futures::Future<Output = error::Result<T>> + Send + Unpin>;
+    type Requests = Rc<RefCell<Vec<(String, Vec<rpc::Value>)>>>;
 
     #[derive(Debug, Default, Clone)]
     pub struct TestTransport {
         asserted: usize,
-        requests: Rc<RefCell<Vec<(String, Vec<rpc::Value>)>>>,
+        requests: Requests,
         responses: Rc<RefCell<VecDeque<rpc::Value>>>,
     }
 
@@ -180,6 +226,48 @@ pub mod tests {
         }
     }
 
+    /// Thread-safe transport returning scripted results in order.
+    ///
+    /// Fails with `Error::Unreachable` once the script is exhausted.
+    #[derive(Debug, Clone, Default)]
+    pub struct FlakyTransport {
+        calls: Arc<Mutex<Vec<String>>>,
+        responses: Arc<Mutex<VecDeque<error::Result<rpc::Value>>>>,
+    }
+
+    impl FlakyTransport {
+        pub fn new(responses: Vec<error::Result<rpc::Value>>) -> Self {
+            Self {
+                calls: Default::default(),
+                responses: Arc::new(Mutex::new(responses.into())),
+            }
+        }
+
+        pub fn calls(&self) -> usize {
+            self.calls.lock().unwrap().len()
+        }
+
+        pub fn methods(&self) -> Vec<String> {
+            self.calls.lock().unwrap().clone()
+        }
+    }
+
+    impl Transport for FlakyTransport {
+        type Out = future::Ready<error::Result<rpc::Value>>;
+
+        fn prepare(&self, method: &str, params: Vec<rpc::Value>) -> (RequestId, rpc::Call) {
+            (1, super::build_request(1, method, params))
+        }
+
+        fn send(&self, _id: RequestId, request: rpc::Call) -> Self::Out {
+            if let rpc::Call::MethodCall(call) = request {
+                self.calls.lock().unwrap().push(call.method);
+            }
+            let response = self.responses.lock().unwrap().pop_front();
+            future::ready(response.unwrap_or(Err(Error::Unreachable)))
+        }
+    }
+
     macro_rules! rpc_test {
         // With parameters (implicit test name)
         (
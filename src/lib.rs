@@ -7,11 +7,17 @@ use jsonrpc_core as rpc;
 #[macro_use]
 pub mod helpers;
 
+pub mod cbft;
 pub mod client;
 pub mod error;
+#[cfg(any(test, feature = "test-utils"))]
+pub mod mock_node;
 pub mod transports;
+pub mod trie;
 pub mod types;
 
+pub use ethabi;
+
 pub use error::Error;
 pub use error::Result;
 
@@ -35,6 +41,7 @@ pub trait Transport: std::fmt::Debug + Clone {
     }
 }
 
+#[allow(clippy::needless_maybe_sized)]
 impl<X, T> Transport for X
 where
     T: Transport + ?Sized,
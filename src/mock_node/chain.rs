@@ -0,0 +1,639 @@
+//! In-memory chain state of the mock node.
+
+use crate::{
+    helpers, trie,
+    types::{
+        Address, Block, Bytes, CallRequest, Log, ReceiptStatus, SyncState, Transaction,
+        TransactionReceipt, TransactionRequest, H160, H2048, H256, U256, U64,
+    },
+};
+use ethereum_types::BloomInput;
+use jsonrpc_core::{Error as RpcError, ErrorCode, Value};
+use serde::{de::DeserializeOwned, Deserialize};
+use std::{
+    collections::{BTreeMap, HashMap},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Prefix of the addresses created by the node.
+const ADDRESS_PREFIX: &str = "lax";
+/// Gas used by a plain transfer.
+const TX_GAS: u64 = 21_000;
+/// Additional gas used by a contract creation.
+const TX_CREATE_GAS: u64 = 32_000;
+/// Gas limit of every block.
+const BLOCK_GAS_LIMIT: u64 = 100_000_000;
+/// Chain id of every ledger.
+const CHAIN_ID: u64 = 201_030;
+/// Version of the protocol, as reported by `juice_protocolVersion`.
+const PROTOCOL_VERSION: u64 = 0x41;
+
+/// Error returned by the node for an invalid request.
+pub(super) fn node_error(message: impl Into<String>) -> RpcError {
+    RpcError {
+        code: ErrorCode::ServerError(-32000),
+        message: message.into(),
+        data: None,
+    }
+}
+
+fn param<T: DeserializeOwned>(params: &[Value], idx: usize) -> Result<T, RpcError> {
+    let value = params.get(idx).cloned().unwrap_or(Value::Null);
+    serde_json::from_value(value)
+        .map_err(|e| RpcError::invalid_params(format!("invalid param {}: {}", idx, e)))
+}
+
+fn to_value<T: serde::Serialize>(value: T) -> Result<Value, RpcError> {
+    Ok(helpers::serialize(&value))
+}
+
+fn hash(parts: &[&[u8]]) -> H256 {
+    H256(helpers::keccak256(&parts.concat()))
+}
+
+fn intrinsic_gas(data: &[u8], create: bool) -> u64 {
+    let data_gas = data
+        .iter()
+        .map(|byte| if *byte == 0 { 4 } else { 16 })
+        .sum::<u64>();
+    TX_GAS + if create { TX_CREATE_GAS } else { 0 } + data_gas
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default()
+}
+
+#[derive(Debug, Clone, Default)]
+struct Account {
+    balance: U256,
+    nonce: u64,
+    code: Bytes,
+}
+
+type State = HashMap<Address, Account>;
+
+/// Filter of `juice_getLogs`.
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LogFilter {
+    from_block: Option<Value>,
+    to_block: Option<Value>,
+    block_hash: Option<H256>,
+    address: Option<OneOrMany<Address>>,
+    topics: Option<Vec<Option<OneOrMany<H256>>>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum OneOrMany<T> {
+    One(T),
+    Many(Vec<T>),
+}
+
+impl<T: PartialEq> OneOrMany<T> {
+    fn contains(&self, value: &T) -> bool {
+        match self {
+            OneOrMany::One(one) => one == value,
+            OneOrMany::Many(many) => many.contains(value),
+        }
+    }
+}
+
+/// A single ledger: its blocks, the state after every block and the mined
+/// transactions.
+#[derive(Debug)]
+struct Ledger {
+    name: String,
+    blocks: Vec<Block<H256>>,
+    states: Vec<State>,
+    transactions: HashMap<H256, (Transaction, TransactionReceipt)>,
+}
+
+impl Ledger {
+    fn new(name: &str, alloc: State) -> Self {
+        let mut ledger = Ledger {
+            name: name.into(),
+            blocks: vec![],
+            states: vec![],
+            transactions: HashMap::new(),
+        };
+        ledger.mine(alloc, vec![]);
+        ledger
+    }
+
+    fn latest(&self) -> &State {
+        self.states.last().expect("genesis block always exists")
+    }
+
+    /// Appends a block including the given transactions, the receipts are
+    /// completed with the block details.
+    ///
+    /// The transactions and receipts roots and the hash are computed as by
+    /// the node, so that the block can be verified. There is no state trie,
+    /// the state root is made up.
+    fn mine(&mut self, state: State, mut txs: Vec<(Transaction, TransactionReceipt)>) -> U64 {
+        let number = U64::from(self.blocks.len());
+        let parent_hash = self
+            .blocks
+            .last()
+            .map(|block| block.hash)
+            .unwrap_or_default();
+
+        let mut gas_used = U256::zero();
+        let mut logs_bloom = H2048::default();
+        for (_, receipt) in txs.iter_mut() {
+            gas_used += receipt.gas_used.unwrap_or_default();
+            logs_bloom.accrue_bloom(&receipt.logs_bloom);
+            receipt.cumulative_gas_used = gas_used;
+        }
+        let transaction_root = trie::ordered_root(
+            txs.iter()
+                .map(|(tx, _)| tx.rlp_bytes().expect("transactions are signed")),
+        );
+        let receipts_root = trie::ordered_root(txs.iter().map(|(_, receipt)| {
+            receipt
+                .rlp_bytes()
+                .expect("receipts have a status and valid log addresses")
+        }));
+        let mut block = Block {
+            parent_hash,
+            coinbase: helpers::bytes_to_address(ADDRESS_PREFIX, &H160::zero()),
+            root: hash(&[
+                self.name.as_bytes(),
+                &number.as_u64().to_be_bytes(),
+                b"state",
+            ]),
+            transaction_root,
+            receipts_root,
+            number: Some(number),
+            gas_limit: BLOCK_GAS_LIMIT.into(),
+            gas_used,
+            timestamp: now().into(),
+            extra: Bytes::default(),
+            logs_bloom,
+            nonce: Bytes(vec![0; 8]),
+            hash: H256::zero(),
+            transactions: txs.iter().map(|(tx, _)| tx.hash).collect(),
+        };
+        let block_hash = block
+            .header()
+            .compute_hash()
+            .expect("the coinbase is a valid address");
+        block.hash = block_hash;
+
+        let mut log_index = 0u64;
+        for (idx, (mut tx, mut receipt)) in txs.into_iter().enumerate() {
+            tx.block_hash = Some(block_hash);
+            tx.block_number = Some(number);
+            tx.transaction_index = Some(idx.into());
+            receipt.block_hash = Some(block_hash);
+            receipt.block_number = Some(number);
+            receipt.transaction_index = idx.into();
+            for log in receipt.logs.iter_mut() {
+                log.block_hash = Some(block_hash);
+                log.block_number = Some(number);
+                log.transaction_index = Some(idx.into());
+                log.log_index = Some(log_index.into());
+                log_index += 1;
+            }
+            self.transactions.insert(tx.hash, (tx, receipt));
+        }
+
+        self.blocks.push(block);
+        self.states.push(state);
+        number
+    }
+
+    /// Resolves a block parameter, `None` when the block does not exist.
+    fn block_index(&self, block: &Value) -> Result<Option<usize>, RpcError> {
+        let latest = self.blocks.len() - 1;
+        match block {
+            Value::Null => Ok(Some(latest)),
+            Value::String(tag) if tag == "latest" || tag == "pending" => Ok(Some(latest)),
+            Value::String(tag) if tag == "earliest" => Ok(Some(0)),
+            Value::Object(object) if object.contains_key("blockHash") => {
+                let hash: H256 = serde_json::from_value(object["blockHash"].clone())
+                    .map_err(|e| RpcError::invalid_params(e.to_string()))?;
+                Ok(self.blocks.iter().position(|block| block.hash == hash))
+            }
+            number => {
+                let number: U64 = serde_json::from_value(number.clone())
+                    .map_err(|e| RpcError::invalid_params(e.to_string()))?;
+                Ok(Some(number.as_usize()).filter(|idx| *idx <= latest))
+            }
+        }
+    }
+
+    /// Returns the state after the given block.
+    fn state_at(&self, block: &Value) -> Result<&State, RpcError> {
+        let idx = self
+            .block_index(block)?
+            .ok_or_else(|| node_error("header not found"))?;
+        Ok(&self.states[idx])
+    }
+
+    fn block_json(&self, idx: Option<usize>, full: bool) -> Result<Value, RpcError> {
+        let block = match idx.and_then(|idx| self.blocks.get(idx)) {
+            Some(block) => block,
+            None => return Ok(Value::Null),
+        };
+        if !full {
+            return to_value(block);
+        }
+        let transactions = block
+            .transactions
+            .iter()
+            .map(|hash| self.transactions[hash].0.clone())
+            .collect::<Vec<_>>();
+        to_value(Block {
+            transactions,
+            parent_hash: block.parent_hash,
+            coinbase: block.coinbase.clone(),
+            root: block.root,
+            transaction_root: block.transaction_root,
+            receipts_root: block.receipts_root,
+            number: block.number,
+            gas_limit: block.gas_limit,
+            gas_used: block.gas_used,
+            timestamp: block.timestamp,
+            extra: block.extra.clone(),
+            logs_bloom: block.logs_bloom,
+            nonce: block.nonce.clone(),
+            hash: block.hash,
+        })
+    }
+
+    fn transaction_in_block(&self, block: Option<usize>, index: U64) -> Value {
+        block
+            .and_then(|idx| self.blocks.get(idx))
+            .and_then(|block| block.transactions.get(index.as_usize()))
+            .map(|hash| helpers::serialize(&self.transactions[hash].0))
+            .unwrap_or(Value::Null)
+    }
+
+    /// Validates and executes the transaction in a new block.
+    fn send(&mut self, request: TransactionRequest, gas_price: U256) -> Result<H256, RpcError> {
+        let mut state = self.latest().clone();
+        let sender = state.entry(request.from.clone()).or_default();
+
+        let nonce = request.nonce.map(|n| n.as_u64()).unwrap_or(sender.nonce);
+        if nonce < sender.nonce {
+            return Err(node_error("nonce too low"));
+        }
+        if nonce > sender.nonce {
+            return Err(node_error("nonce too high"));
+        }
+
+        if let Some(ref to) = request.to {
+            helpers::address_to_bytes(to)
+                .map_err(|_| RpcError::invalid_params(format!("invalid address: {}", to)))?;
+        }
+
+        let data = request.data.clone().unwrap_or_default();
+        let gas_used = intrinsic_gas(&data.0, request.to.is_none());
+        let gas = request.gas.unwrap_or_else(|| gas_used.into());
+        if gas < gas_used.into() {
+            return Err(node_error("intrinsic gas too low"));
+        }
+        if gas > BLOCK_GAS_LIMIT.into() {
+            return Err(node_error("exceeds block gas limit"));
+        }
+        let gas_price = request.gas_price.unwrap_or(gas_price);
+        let value = request.value.unwrap_or_default();
+        let cost = gas
+            .checked_mul(gas_price)
+            .and_then(|fee| fee.checked_add(value))
+            .filter(|cost| *cost <= sender.balance);
+        if cost.is_none() {
+            return Err(node_error("insufficient funds for gas * price + value"));
+        }
+
+        sender.balance -= U256::from(gas_used) * gas_price + value;
+        sender.nonce += 1;
+
+        let (recipient, contract_address) = match request.to {
+            Some(ref to) => (to.clone(), None),
+            None => {
+                let prefix = bech32::decode(&request.from)
+                    .map(|(hrp, _, _)| hrp)
+                    .unwrap_or_else(|_| ADDRESS_PREFIX.into());
+                let hash = hash(&[request.from.as_bytes(), &nonce.to_be_bytes()]);
+                let address = helpers::bytes_to_address(&prefix, &H160::from_slice(&hash[12..]));
+                (address.clone(), Some(address))
+            }
+        };
+        let recipient_account = state.entry(recipient.clone()).or_default();
+        recipient_account.balance += value;
+        if contract_address.is_some() {
+            recipient_account.code = data.clone();
+        }
+
+        // The node holds no keys: the signature is made up from the sender
+        // and the nonce, so that the transaction can be encoded and hashed.
+        let r = hash(&[
+            self.name.as_bytes(),
+            request.from.as_bytes(),
+            &nonce.to_be_bytes(),
+        ]);
+        let mut tx = Transaction {
+            hash: H256::zero(),
+            nonce: Some(nonce.into()),
+            block_hash: None,
+            block_number: None,
+            transaction_index: None,
+            from: Some(request.from),
+            to: request.to.clone(),
+            value: Some(value),
+            gas_price,
+            gas,
+            input: data.clone(),
+            v: Some((CHAIN_ID * 2 + 35).into()),
+            r: Some(U256::from_big_endian(r.as_bytes())),
+            s: Some(U256::from_big_endian(&helpers::keccak256(r.as_bytes()))),
+            raw: None,
+        };
+        let tx_hash = H256(helpers::keccak256(
+            &tx.rlp_bytes().map_err(|e| node_error(e.to_string()))?,
+        ));
+        tx.hash = tx_hash;
+
+        // Calls with data emit a single log, so that logs can be queried.
+        let mut logs_bloom = H2048::default();
+        let logs = match request.to {
+            Some(to) if !data.0.is_empty() => {
+                let topic = H256(helpers::keccak256(&data.0));
+                if let Ok(address) = helpers::address_to_bytes(&to) {
+                    logs_bloom.accrue(BloomInput::Raw(address.as_bytes()));
+                }
+                logs_bloom.accrue(BloomInput::Raw(topic.as_bytes()));
+                vec![Log {
+                    address: to,
+                    topics: vec![topic],
+                    data,
+                    block_hash: None,
+                    block_number: None,
+                    transaction_hash: Some(tx_hash),
+                    transaction_index: None,
+                    log_index: None,
+                    transaction_log_index: Some(U256::zero()),
+                    removed: Some(false),
+                }]
+            }
+            _ => vec![],
+        };
+
+        let receipt = TransactionReceipt {
+            transaction_hash: tx_hash,
+            transaction_index: U64::zero(),
+            block_hash: None,
+            block_number: None,
+            cumulative_gas_used: U256::zero(),
+            gas_used: Some(gas_used.into()),
+            contract_address,
+            logs,
+            status: Some(ReceiptStatus::Success),
+            root: None,
+            logs_bloom,
+        };
+        self.mine(state, vec![(tx, receipt)]);
+        Ok(tx_hash)
+    }
+
+    /// Includes a signed transaction in a new block.
+    ///
+    /// The signature is not checked and the sender is not recovered, so the
+    /// state is left untouched. The same transaction is only included once.
+    fn send_raw(&mut self, raw: Bytes, gas_price: U256) -> Result<H256, RpcError> {
+        let tx_hash = H256(helpers::keccak256(&raw.0));
+        if self.transactions.contains_key(&tx_hash) {
+            return Err(node_error("already known"));
+        }
+        let tx = Transaction {
+            hash: tx_hash,
+            nonce: None,
+            block_hash: None,
+            block_number: None,
+            transaction_index: None,
+            from: None,
+            to: None,
+            value: None,
+            gas_price,
+            gas: TX_GAS.into(),
+            input: Bytes::default(),
+            v: None,
+            r: None,
+            s: None,
+            raw: Some(raw),
+        };
+        let receipt = TransactionReceipt {
+            transaction_hash: tx_hash,
+            transaction_index: U64::zero(),
+            block_hash: None,
+            block_number: None,
+            cumulative_gas_used: U256::zero(),
+            gas_used: Some(TX_GAS.into()),
+            contract_address: None,
+            logs: vec![],
+            status: Some(ReceiptStatus::Success),
+            root: None,
+            logs_bloom: H2048::default(),
+        };
+        let state = self.latest().clone();
+        self.mine(state, vec![(tx, receipt)]);
+        Ok(tx_hash)
+    }
+
+    fn logs(&self, filter: LogFilter) -> Result<Vec<Log>, RpcError> {
+        let (from, to) = match filter.block_hash {
+            Some(hash) => {
+                let idx = self.blocks.iter().position(|block| block.hash == hash);
+                let idx = idx.ok_or_else(|| node_error("unknown block"))?;
+                (idx, idx)
+            }
+            None => {
+                let from = filter.from_block.unwrap_or(Value::Null);
+                let to = filter.to_block.unwrap_or(Value::Null);
+                let latest = self.blocks.len() - 1;
+                (
+                    self.block_index(&from)?.unwrap_or(latest + 1),
+                    self.block_index(&to)?.unwrap_or(latest),
+                )
+            }
+        };
+
+        let address = filter.address;
+        let topics = filter.topics.unwrap_or_default();
+        let to = to.min(self.blocks.len() - 1);
+        if from > to {
+            return Ok(vec![]);
+        }
+        let logs = self.blocks[from..=to]
+            .iter()
+            .flat_map(|block| block.transactions.iter())
+            .flat_map(|hash| self.transactions[hash].1.logs.iter())
+            .filter(|log| match address {
+                Some(ref address) => address.contains(&log.address),
+                None => true,
+            })
+            .filter(|log| {
+                topics.iter().enumerate().all(|(idx, topic)| match topic {
+                    Some(topic) => log.topics.get(idx).is_some_and(|t| topic.contains(t)),
+                    None => true,
+                })
+            })
+            .cloned()
+            .collect();
+        Ok(logs)
+    }
+}
+
+/// All the ledgers of the mock node.
+#[derive(Debug)]
+pub(super) struct Chain {
+    ledgers: BTreeMap<String, Ledger>,
+    alloc: State,
+    gas_price: U256,
+}
+
+impl Chain {
+    pub fn new(gas_price: U256) -> Self {
+        Chain {
+            ledgers: BTreeMap::new(),
+            alloc: State::new(),
+            gas_price,
+        }
+    }
+
+    pub fn add_ledger(&mut self, name: &str) {
+        let alloc = self.alloc.clone();
+        self.ledgers
+            .entry(name.into())
+            .or_insert_with(|| Ledger::new(name, alloc));
+    }
+
+    /// Funds the account in the genesis state of every ledger.
+    pub fn fund(&mut self, address: &str, amount: U256) {
+        self.alloc.entry(address.into()).or_default().balance += amount;
+        for ledger in self.ledgers.values_mut() {
+            for state in ledger.states.iter_mut() {
+                state.entry(address.into()).or_default().balance += amount;
+            }
+        }
+    }
+
+    pub fn set_gas_price(&mut self, gas_price: U256) {
+        self.gas_price = gas_price;
+    }
+
+    /// Mines an empty block on the given ledger.
+    pub fn mine(&mut self, ledger: &str) -> Option<U64> {
+        let ledger = self.ledgers.get_mut(ledger)?;
+        let state = ledger.latest().clone();
+        Some(ledger.mine(state, vec![]))
+    }
+
+    fn ledger(&mut self, params: &[Value]) -> Result<&mut Ledger, RpcError> {
+        let name: String = param(params, 0)?;
+        self.ledgers
+            .get_mut(&name)
+            .ok_or_else(|| node_error(format!("unknown ledger: {}", name)))
+    }
+
+    /// Handles a single request.
+    pub fn call(&mut self, method: &str, params: &[Value]) -> Result<Value, RpcError> {
+        match method {
+            "net_version" => return to_value(CHAIN_ID.to_string()),
+            "net_peerCount" => return to_value(U64::zero()),
+            "net_listening" => return to_value(true),
+            "web3_clientVersion" => {
+                return to_value(concat!("juice-mock-node/v", env!("CARGO_PKG_VERSION")))
+            }
+            _ => {}
+        }
+
+        let gas_price = self.gas_price;
+        let ledger = self.ledger(params)?;
+        match method {
+            "juice_blockNumber" => to_value(U64::from(ledger.blocks.len() - 1)),
+            "juice_gasPrice" => to_value(gas_price),
+            "juice_chainId" => to_value(U64::from(CHAIN_ID)),
+            "juice_protocolVersion" => to_value(U64::from(PROTOCOL_VERSION)),
+            "juice_syncing" => to_value(SyncState::NotSyncing),
+            "juice_getBlockByNumber" => {
+                let idx = ledger.block_index(&param(params, 1)?)?;
+                ledger.block_json(idx, param(params, 2)?)
+            }
+            "juice_getBlockByHash" => {
+                let hash: H256 = param(params, 1)?;
+                let idx = ledger.blocks.iter().position(|block| block.hash == hash);
+                ledger.block_json(idx, param(params, 2)?)
+            }
+            "juice_getBlockTransactionCountByHash" => {
+                let hash: H256 = param(params, 1)?;
+                let block = ledger.blocks.iter().find(|block| block.hash == hash);
+                Ok(block
+                    .map(|block| helpers::serialize(&U64::from(block.transactions.len())))
+                    .unwrap_or(Value::Null))
+            }
+            "juice_getBlockTransactionCountByNumber" => {
+                let idx = ledger.block_index(&param(params, 1)?)?;
+                Ok(idx
+                    .map(|idx| {
+                        helpers::serialize(&U64::from(ledger.blocks[idx].transactions.len()))
+                    })
+                    .unwrap_or(Value::Null))
+            }
+            "juice_getTransactionByBlockNumberAndIndex" => {
+                let idx = ledger.block_index(&param(params, 1)?)?;
+                Ok(ledger.transaction_in_block(idx, param(params, 2)?))
+            }
+            "juice_getTransactionByBlockHashAndIndex" => {
+                let hash: H256 = param(params, 1)?;
+                let idx = ledger.blocks.iter().position(|block| block.hash == hash);
+                Ok(ledger.transaction_in_block(idx, param(params, 2)?))
+            }
+            "juice_getTransactionByHash" => {
+                let hash: H256 = param(params, 1)?;
+                to_value(ledger.transactions.get(&hash).map(|(tx, _)| tx))
+            }
+            "juice_getTransactionReceipt" => {
+                let hash: H256 = param(params, 1)?;
+                to_value(ledger.transactions.get(&hash).map(|(_, receipt)| receipt))
+            }
+            "juice_getBalance" | "juice_getTransactionCount" | "juice_getCode" => {
+                let address: Address = param(params, 1)?;
+                let account = ledger
+                    .state_at(&param(params, 2)?)?
+                    .get(&address)
+                    .cloned()
+                    .unwrap_or_default();
+                match method {
+                    "juice_getBalance" => to_value(account.balance),
+                    "juice_getTransactionCount" => to_value(U64::from(account.nonce)),
+                    _ => to_value(account.code),
+                }
+            }
+            "juice_getStorageAt" => {
+                ledger.state_at(&param(params, 3)?)?;
+                to_value(Bytes(vec![0; 32]))
+            }
+            "juice_call" => {
+                let _: CallRequest = param(params, 1)?;
+                ledger.state_at(&param(params, 2)?)?;
+                to_value(Bytes::default())
+            }
+            "juice_estimateGas" => {
+                let request: CallRequest = param(params, 1)?;
+                let data = request.data.unwrap_or_default();
+                to_value(U64::from(intrinsic_gas(&data.0, request.to.is_none())))
+            }
+            "juice_sendTransaction" => to_value(ledger.send(param(params, 1)?, gas_price)?),
+            "juice_sendRawTransaction" => to_value(ledger.send_raw(param(params, 1)?, gas_price)?),
+            "juice_getLogs" => to_value(ledger.logs(param(params, 1)?)?),
+            _ => Err(RpcError::method_not_found()),
+        }
+    }
+}
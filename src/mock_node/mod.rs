@@ -0,0 +1,470 @@
+//! In-process Juice node for integration tests.
+//!
+//! Available with the `test-utils` feature. The node simulates several
+//! ledgers with an in-memory chain: every transaction is executed in its own
+//! block, balances, nonces, receipts and logs can be queried with the usual
+//! `juice_*` methods. There is no EVM; calls with data to an account emit a
+//! single log from that account, with the hash of the data as topic.
+//!
+//! Blocks carry the transactions and receipts roots and the hash computed as
+//! by a real node, so they pass the `verify_*` checks of the SDK.
+
+mod chain;
+
+use crate::{
+    error::{Error, Result},
+    helpers, rpc,
+    types::{Address, U256, U64},
+};
+use chain::Chain;
+use std::{
+    net::SocketAddr,
+    sync::{Arc, Mutex},
+};
+use tokio::sync::oneshot;
+
+/// Name of the ledger created by `MockNode::new`.
+pub const DEFAULT_LEDGER: &str = "sys";
+/// Gas price used when the transaction does not set one.
+pub const DEFAULT_GAS_PRICE: u64 = 1_000_000_000;
+
+/// In-memory Juice node serving JSON-RPC over HTTP.
+///
+/// Clones share the same chain, so a clone may be kept to fund accounts or
+/// mine blocks while the node is served.
+///
+/// ```no_run
+/// # async fn example() -> juice_sdk_rs::Result<()> {
+/// use juice_sdk_rs::{client::Client, mock_node::MockNode, transports::Http};
+///
+/// let node = MockNode::new()
+///     .ledger("ledger1")
+///     .fund("lax18qg084alcnuv2jjdx4u68vw4ve8lffm0ncrzww", 1_000_000_000_000_000_000u64.into());
+/// let server = node.serve("127.0.0.1:0".parse().unwrap())?;
+///
+/// let client = Client::new(Http::new(&server.url())?, true);
+/// assert_eq!(client.ledger("ledger1").block_number().await?.as_u64(), 0);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct MockNode {
+    chain: Arc<Mutex<Chain>>,
+}
+
+impl Default for MockNode {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MockNode {
+    /// Create a new node with the `sys` ledger.
+    pub fn new() -> Self {
+        let mut chain = Chain::new(DEFAULT_GAS_PRICE.into());
+        chain.add_ledger(DEFAULT_LEDGER);
+        MockNode {
+            chain: Arc::new(Mutex::new(chain)),
+        }
+    }
+
+    /// Add a ledger, starting from its genesis block.
+    pub fn ledger(self, name: &str) -> Self {
+        self.chain().add_ledger(name);
+        self
+    }
+
+    /// Credit the account in the genesis state of every ledger.
+    pub fn fund(self, address: &str, amount: U256) -> Self {
+        self.chain().fund(address, amount);
+        self
+    }
+
+    /// Set the gas price returned by `juice_gasPrice` and used by default.
+    pub fn gas_price(self, gas_price: U256) -> Self {
+        self.chain().set_gas_price(gas_price);
+        self
+    }
+
+    /// Mine an empty block on the given ledger, returns its number.
+    pub fn mine(&self, ledger: &str) -> Option<U64> {
+        self.chain().mine(ledger)
+    }
+
+    /// Returns the balance of the account on the latest block of the ledger.
+    pub fn balance(&self, ledger: &str, address: &Address) -> Result<U256> {
+        let params = [helpers::serialize(&ledger), helpers::serialize(address)];
+        let balance = self.chain().call("juice_getBalance", &params)?;
+        helpers::decode(balance)
+    }
+
+    /// Start serving the node over HTTP on the given address.
+    ///
+    /// Must be called within a Tokio runtime. The server stops when the
+    /// returned handle is dropped.
+    pub fn serve(&self, addr: SocketAddr) -> Result<MockServer> {
+        use hyper::service::{make_service_fn, service_fn};
+
+        let node = self.clone();
+        let service = make_service_fn(move |_| {
+            let node = node.clone();
+            async move {
+                Ok::<_, hyper::Error>(service_fn(move |req| {
+                    let node = node.clone();
+                    async move {
+                        let body = hyper::body::to_bytes(req.into_body()).await?;
+                        let response = hyper::Response::builder()
+                            .header(hyper::header::CONTENT_TYPE, "application/json")
+                            .body(hyper::Body::from(node.handle(&body)))
+                            .expect("Response is valid.");
+                        Ok::<_, hyper::Error>(response)
+                    }
+                }))
+            }
+        });
+
+        let server = hyper::Server::try_bind(&addr)
+            .map_err(|e| Error::Transport(format!("failed to bind {}: {}", addr, e)))?
+            .serve(service);
+        let addr = server.local_addr();
+        let (shutdown, stopped) = oneshot::channel();
+        tokio::spawn(server.with_graceful_shutdown(async {
+            stopped.await.ok();
+        }));
+        log::debug!("mock node listening on {}", addr);
+
+        Ok(MockServer {
+            addr,
+            shutdown: Some(shutdown),
+        })
+    }
+
+    /// Handles a JSON-RPC request, single or batch, returns the response.
+    pub fn handle(&self, request: &[u8]) -> String {
+        let request = match helpers::arbitrary_precision_deserialize_workaround(request) {
+            Ok(request) => request,
+            Err(_) => {
+                let response =
+                    rpc::Response::from(rpc::Error::parse_error(), Some(rpc::Version::V2));
+                return helpers::to_string(&response);
+            }
+        };
+        let response = match request {
+            rpc::Request::Single(call) => self.handle_call(call).map(rpc::Response::Single),
+            rpc::Request::Batch(calls) => Some(rpc::Response::Batch(
+                calls
+                    .into_iter()
+                    .filter_map(|call| self.handle_call(call))
+                    .collect(),
+            )),
+        };
+        response
+            .map(|response| helpers::to_string(&response))
+            .unwrap_or_default()
+    }
+
+    fn handle_call(&self, call: rpc::Call) -> Option<rpc::Output> {
+        match call {
+            rpc::Call::MethodCall(call) => {
+                let params = match call.params {
+                    rpc::Params::Array(params) => params,
+                    rpc::Params::Map(map) => vec![rpc::Value::Object(map)],
+                    rpc::Params::None => vec![],
+                };
+                let result = self.chain().call(&call.method, &params);
+                if let Err(ref err) = result {
+                    log::debug!("{} failed: {}", call.method, err.message);
+                }
+                Some(rpc::Output::from(result, call.id, call.jsonrpc))
+            }
+            rpc::Call::Notification(_) => None,
+            rpc::Call::Invalid { id } => Some(rpc::Output::from(
+                Err(rpc::Error::invalid_request()),
+                id,
+                Some(rpc::Version::V2),
+            )),
+        }
+    }
+
+    fn chain(&self) -> std::sync::MutexGuard<'_, Chain> {
+        self.chain.lock().expect("chain lock poisoned")
+    }
+}
+
+/// Handle of a running `MockNode` server, stops it when dropped.
+#[derive(Debug)]
+pub struct MockServer {
+    addr: SocketAddr,
+    shutdown: Option<oneshot::Sender<()>>,
+}
+
+impl MockServer {
+    /// Returns the address the server listens on.
+    pub fn addr(&self) -> SocketAddr {
+        self.addr
+    }
+
+    /// Returns the URL to give to the HTTP transport.
+    pub fn url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        if let Some(shutdown) = self.shutdown.take() {
+            shutdown.send(()).ok();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        client::Client,
+        error::NodeError,
+        transports::Http,
+        types::{BlockNumber, BloomExt, Bytes, Log, ReceiptStatus, TransactionRequest, H160, H256},
+        Transport,
+    };
+    use serde_json::json;
+
+    const ALICE: &str = "lax18qg084alcnuv2jjdx4u68vw4ve8lffm0ncrzww";
+
+    fn bob() -> Address {
+        helpers::bytes_to_address("lax", &H160::repeat_byte(0xb0))
+    }
+
+    fn start() -> (MockNode, MockServer, Client<Http>) {
+        let node = MockNode::new()
+            .ledger("ledger1")
+            .fund(ALICE, U256::exp10(18));
+        let server = node.serve("127.0.0.1:0".parse().unwrap()).unwrap();
+        let client = Client::new(Http::new(&server.url()).unwrap(), true);
+        (node, server, client)
+    }
+
+    fn transfer(value: u64) -> TransactionRequest {
+        TransactionRequest {
+            from: ALICE.into(),
+            to: Some(bob()),
+            value: Some(value.into()),
+            gas_price: Some(1.into()),
+            ..Default::default()
+        }
+    }
+
+    #[tokio::test]
+    async fn should_execute_transfers() {
+        // given
+        let (node, _server, client) = start();
+        let sys = client.ledger("sys");
+
+        // when
+        let hash = sys.send_transaction(transfer(1_000)).await.unwrap();
+        let receipt = sys.transaction_receipt(hash).await.unwrap().unwrap();
+        let tx = sys.transaction_by_hash(hash).await.unwrap().unwrap();
+        let block = sys
+            .block_by_number_txs(BlockNumber::Latest)
+            .await
+            .unwrap()
+            .unwrap();
+
+        // then
+        assert_eq!(sys.block_number().await.unwrap(), 1.into());
+        assert_eq!(receipt.status, Some(ReceiptStatus::Success));
+        assert_eq!(receipt.block_number, Some(1.into()));
+        assert_eq!(receipt.gas_used, Some(21_000.into()));
+        assert_eq!(tx.from.as_deref(), Some(ALICE));
+        assert_eq!(block.transactions, vec![tx.clone()]);
+        assert_eq!(
+            sys.transaction_count_by_number(1.into()).await.unwrap(),
+            1.into()
+        );
+        assert_eq!(sys.transaction_count(block.hash).await.unwrap(), 1.into());
+        assert_eq!(
+            sys.transaction_in_block_by_number(BlockNumber::Latest, 0.into())
+                .await
+                .unwrap(),
+            Some(tx)
+        );
+        assert_eq!(sys.chain_id().await.unwrap(), 201_030.into());
+        assert_eq!(client.network_id().await.unwrap(), "201030");
+        assert!(client.is_listening().await.unwrap());
+        let header = sys.header_by_number(BlockNumber::Latest).await.unwrap();
+        assert_eq!(header.map(|header| header.hash), Some(block.hash));
+        assert_eq!(sys.nonce_at(ALICE.into(), None).await.unwrap(), 1.into());
+        assert_eq!(sys.balance_at(bob(), None).await.unwrap(), 1_000.into());
+        assert_eq!(
+            node.balance("sys", &ALICE.into()).unwrap(),
+            U256::exp10(18) - 22_000
+        );
+        assert_eq!(
+            sys.balance_at(ALICE.into(), Some(BlockNumber::Earilest))
+                .await
+                .unwrap(),
+            U256::exp10(18)
+        );
+        // ledgers are independent
+        let ledger1 = client.ledger("ledger1");
+        assert_eq!(ledger1.block_number().await.unwrap(), 0.into());
+        assert_eq!(ledger1.balance_at(bob(), None).await.unwrap(), 0.into());
+    }
+
+    #[tokio::test]
+    async fn should_reject_invalid_transactions() {
+        let (_node, _server, client) = start();
+        let sys = client.ledger("sys");
+        fn node_error<T: std::fmt::Debug>(result: Result<T>) -> Option<NodeError> {
+            result.unwrap_err().node_error().cloned()
+        }
+
+        let mut stale = transfer(1);
+        stale.nonce = Some(0.into());
+        sys.send_transaction(stale.clone()).await.unwrap();
+
+        assert_eq!(
+            node_error(sys.send_transaction(stale).await),
+            Some(NodeError::NonceTooLow)
+        );
+        assert_eq!(
+            node_error(sys.send_transaction(transfer(u64::MAX)).await),
+            Some(NodeError::InsufficientBalance)
+        );
+        assert_eq!(
+            node_error(client.ledger("nope").block_number().await),
+            Some(NodeError::UnknownLedger)
+        );
+        // gas * price + value overflows
+        let mut overflow = transfer(0);
+        overflow.value = Some(U256::MAX);
+        assert_eq!(
+            node_error(sys.send_transaction(overflow.clone()).await),
+            Some(NodeError::InsufficientBalance)
+        );
+        overflow.value = None;
+        overflow.gas_price = Some(U256::MAX);
+        assert_eq!(
+            node_error(sys.send_transaction(overflow).await),
+            Some(NodeError::InsufficientBalance)
+        );
+        let raw = Bytes::from(vec![0xc0]);
+        sys.send_raw_transaction(raw.clone()).await.unwrap();
+        assert_eq!(
+            node_error(sys.send_raw_transaction(raw).await),
+            Some(NodeError::AlreadyKnown)
+        );
+        assert_eq!(sys.block_number().await.unwrap(), 2.into());
+    }
+
+    #[tokio::test]
+    async fn should_serve_verifiable_blocks() {
+        // given
+        let (_node, _server, client) = start();
+        let sys = client.ledger("sys");
+        let mut call = transfer(0);
+        call.data = Some(vec![1, 2, 3].into());
+        sys.send_transaction(call).await.unwrap();
+        sys.send_raw_transaction(vec![0xc0].into()).await.unwrap();
+
+        for number in 0..3u64 {
+            // when
+            let block = sys
+                .block_by_number_txs(number.into())
+                .await
+                .unwrap()
+                .unwrap();
+            let mut receipts = vec![];
+            for tx in &block.transactions {
+                receipts.push(sys.transaction_receipt(tx.hash).await.unwrap().unwrap());
+            }
+
+            // then
+            assert_eq!(block.header().verify_hash(), Ok(true));
+            assert_eq!(block.verify_transactions(), Ok(true));
+            assert_eq!(block.verify_receipts(&receipts), Ok(true));
+            for tx in &block.transactions {
+                let rlp = tx.rlp_bytes().unwrap();
+                assert_eq!(tx.hash, H256(helpers::keccak256(&rlp)));
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn should_return_no_logs_for_reversed_range() {
+        // given
+        let (node, _server, client) = start();
+        node.mine("sys");
+        node.mine("sys");
+        node.mine("sys");
+
+        // when
+        let logs = client
+            .transport()
+            .execute(
+                "juice_getLogs",
+                vec![json!("sys"), json!({"fromBlock": "0x3", "toBlock": "0x1"})],
+            )
+            .await
+            .and_then(helpers::decode::<Vec<Log>>);
+
+        // then
+        assert_eq!(logs, Ok(vec![]));
+        // the node is still serving requests
+        let block_number = client.ledger("sys").block_number().await;
+        assert_eq!(block_number, Ok(3.into()));
+    }
+
+    #[tokio::test]
+    async fn should_filter_logs() {
+        // given
+        let (node, _server, client) = start();
+        let sys = client.ledger("sys");
+        let mut call = transfer(0);
+        call.data = Some(vec![1, 2, 3].into());
+        let hash = sys.send_transaction(call).await.unwrap();
+        node.mine("sys");
+        sys.send_transaction(transfer(0)).await.unwrap();
+
+        // when
+        let topic = H256(helpers::keccak256(&[1, 2, 3]));
+        let logs = client
+            .transport()
+            .execute(
+                "juice_getLogs",
+                vec![
+                    json!("sys"),
+                    json!({"fromBlock": "earliest", "address": bob(), "topics": [topic]}),
+                ],
+            )
+            .await
+            .and_then(helpers::decode::<Vec<Log>>)
+            .unwrap();
+        let other = client
+            .transport()
+            .execute(
+                "juice_getLogs",
+                vec![json!("sys"), json!({"fromBlock": "0x2", "address": bob()})],
+            )
+            .await
+            .and_then(helpers::decode::<Vec<Log>>)
+            .unwrap();
+
+        // then
+        assert_eq!(logs.len(), 1);
+        assert_eq!(logs[0].transaction_hash, Some(hash));
+        assert_eq!(logs[0].data, vec![1, 2, 3].into());
+        assert!(other.is_empty());
+        let receipt = sys.transaction_receipt(hash).await.unwrap().unwrap();
+        assert_eq!(receipt.logs, logs);
+        assert_eq!(sys.block_number().await.unwrap(), 3.into());
+        // only the block of the call may contain the log
+        let header = sys.header_by_number(1.into()).await.unwrap().unwrap();
+        assert_eq!(header.logs_bloom, receipt.logs_bloom);
+        assert_eq!(header.logs_bloom.contains_address(&bob()), Ok(true));
+        assert!(header.logs_bloom.contains_topic(&topic));
+        let header = sys.header_by_number(3.into()).await.unwrap().unwrap();
+        assert_eq!(header.logs_bloom.contains_address(&bob()), Ok(false));
+    }
+}
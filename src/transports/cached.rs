@@ -0,0 +1,403 @@
+use crate::{error::Result, helpers, RequestId, Transport};
+use futures::future::BoxFuture;
+use jsonrpc_core::types::{Call, Params, Value};
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+/// Methods whose result never changes once it is not `null`.
+const IMMUTABLE_METHODS: &[&str] = &[
+    "juice_getBlockByHash",
+    "juice_getBlockTransactionCountByHash",
+    "juice_getTransactionByBlockHashAndIndex",
+    "juice_getTransactionReceipt",
+];
+
+/// Methods whose result never changes when queried at a block number.
+/// The number is the parameter at the given index.
+const AT_BLOCK_METHODS: &[(&str, usize)] = &[
+    ("juice_getBlockByNumber", 1),
+    ("juice_getBlockTransactionCountByNumber", 1),
+    ("juice_getTransactionByBlockNumberAndIndex", 1),
+    ("juice_getBalance", 2),
+    ("juice_getCode", 2),
+    ("juice_getTransactionCount", 2),
+    ("juice_call", 2),
+    ("juice_getStorageAt", 3),
+];
+
+/// Returns the cache key of the call if its result may be cached.
+///
+/// Blocks are final once produced by CBFT, so anything addressed by hash or
+/// by an explicit block number never changes.
+fn cache_key(call: &Call) -> Option<String> {
+    let (method, params) = match call {
+        Call::MethodCall(call) => match call.params {
+            Params::Array(ref params) => (call.method.as_str(), params),
+            _ => return None,
+        },
+        _ => return None,
+    };
+    let cacheable = IMMUTABLE_METHODS.contains(&method)
+        || method == "juice_getTransactionByHash"
+        || AT_BLOCK_METHODS
+            .iter()
+            .any(|(name, idx)| *name == method && is_block_number(params.get(*idx)));
+    if !cacheable {
+        return None;
+    }
+    Some(helpers::to_string(&(method, params)))
+}
+
+/// Returns true if the parameter is an explicit block number.
+fn is_block_number(param: Option<&Value>) -> bool {
+    matches!(param, Some(Value::String(number)) if number.starts_with("0x"))
+}
+
+/// Returns true if the result of the call may be cached.
+fn is_final(method: &str, result: &Value) -> bool {
+    match result {
+        Value::Null => false,
+        // Pending transactions are returned without block hash.
+        Value::Object(tx) if method == "juice_getTransactionByHash" => {
+            matches!(tx.get("blockHash"), Some(hash) if !hash.is_null())
+        }
+        _ => true,
+    }
+}
+
+/// Statistics of the cache.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// Number of requests served from the cache
+    pub hits: u64,
+    /// Number of cacheable requests sent to the node
+    pub misses: u64,
+    /// Number of entries removed to make room for new ones
+    pub evictions: u64,
+    /// Number of entries in the cache
+    pub entries: usize,
+}
+
+/// Least recently used map of cache keys to results.
+#[derive(Debug, Default)]
+struct Lru {
+    entries: HashMap<String, (Value, u64)>,
+    order: BTreeMap<u64, String>,
+    tick: u64,
+}
+
+impl Lru {
+    fn get(&mut self, key: &str) -> Option<Value> {
+        self.tick += 1;
+        let tick = self.tick;
+        let (value, used) = self.entries.get_mut(key)?;
+        self.order.remove(used);
+        self.order.insert(tick, key.to_owned());
+        *used = tick;
+        Some(value.clone())
+    }
+
+    /// Inserts the entry, returns the number of evicted entries.
+    fn insert(&mut self, key: String, value: Value, capacity: usize) -> u64 {
+        self.tick += 1;
+        if let Some((_, used)) = self.entries.insert(key.clone(), (value, self.tick)) {
+            self.order.remove(&used);
+        }
+        self.order.insert(self.tick, key);
+
+        let mut evicted = 0;
+        while self.entries.len() > capacity {
+            let oldest = match self.order.keys().next() {
+                Some(oldest) => *oldest,
+                None => break,
+            };
+            if let Some(key) = self.order.remove(&oldest) {
+                self.entries.remove(&key);
+                evicted += 1;
+            }
+        }
+        evicted
+    }
+
+    /// Returns the entries from the least to the most recently used.
+    fn entries(&self) -> Vec<(&String, &Value)> {
+        self.order
+            .values()
+            .filter_map(|key| self.entries.get(key).map(|(value, _)| (key, value)))
+            .collect()
+    }
+}
+
+#[derive(Debug)]
+struct Inner {
+    lru: Mutex<Lru>,
+    capacity: usize,
+    path: Option<PathBuf>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+}
+
+/// Transport caching the results of queries which never change.
+///
+/// Blocks and transactions by hash, receipts and state queries at an
+/// explicit block number are kept in a bounded LRU cache, `null` results
+/// are never cached. The cache may be persisted to a JSON file.
+#[derive(Debug, Clone)]
+pub struct Cached<T> {
+    inner: T,
+    cache: Arc<Inner>,
+}
+
+impl<T: Transport> Cached<T> {
+    /// Create a new transport caching up to `capacity` results in memory.
+    pub fn new(inner: T, capacity: usize) -> Self {
+        Self::with_path(inner, capacity, None)
+    }
+
+    /// Create a new transport caching up to `capacity` results, persisted
+    /// to the given file by `save`. Entries already saved to the file are
+    /// loaded.
+    pub fn persistent(inner: T, capacity: usize, path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref().to_owned();
+        let cached = Self::with_path(inner, capacity, Some(path.clone()));
+        if path.exists() {
+            let entries: Vec<(String, Value)> = serde_json::from_slice(&fs::read(&path)?)?;
+            let mut lru = cached.cache.lru.lock().expect("cache lock poisoned");
+            for (key, value) in entries {
+                lru.insert(key, value, cached.cache.capacity);
+            }
+        }
+        Ok(cached)
+    }
+
+    fn with_path(inner: T, capacity: usize, path: Option<PathBuf>) -> Self {
+        Self {
+            inner,
+            cache: Arc::new(Inner {
+                lru: Default::default(),
+                capacity: capacity.max(1),
+                path,
+                hits: Default::default(),
+                misses: Default::default(),
+                evictions: Default::default(),
+            }),
+        }
+    }
+
+    /// Writes the cached results to the file given to `persistent`.
+    pub fn save(&self) -> Result<()> {
+        let path = match self.cache.path {
+            Some(ref path) => path,
+            None => return Ok(()),
+        };
+        let data = {
+            let lru = self.cache.lru.lock().expect("cache lock poisoned");
+            serde_json::to_vec(&lru.entries())?
+        };
+        fs::write(path, data)?;
+        Ok(())
+    }
+
+    /// Returns the statistics of the cache.
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.cache.hits.load(Ordering::Relaxed),
+            misses: self.cache.misses.load(Ordering::Relaxed),
+            evictions: self.cache.evictions.load(Ordering::Relaxed),
+            entries: self
+                .cache
+                .lru
+                .lock()
+                .expect("cache lock poisoned")
+                .entries
+                .len(),
+        }
+    }
+
+    /// Removes all the cached results.
+    pub fn clear(&self) {
+        *self.cache.lru.lock().expect("cache lock poisoned") = Default::default();
+    }
+}
+
+impl<T> Transport for Cached<T>
+where
+    T: Transport + Send + Sync + 'static,
+    T::Out: Send,
+{
+    type Out = BoxFuture<'static, Result<Value>>;
+
+    fn prepare(&self, method: &str, params: Vec<Value>) -> (RequestId, Call) {
+        self.inner.prepare(method, params)
+    }
+
+    fn send(&self, id: RequestId, request: Call) -> Self::Out {
+        let key = cache_key(&request);
+        if let Some(ref key) = key {
+            let cached = self.cache.lru.lock().expect("cache lock poisoned").get(key);
+            if let Some(value) = cached {
+                self.cache.hits.fetch_add(1, Ordering::Relaxed);
+                return Box::pin(futures::future::ready(Ok(value)));
+            }
+            self.cache.misses.fetch_add(1, Ordering::Relaxed);
+        }
+
+        let method = helpers::call_method(&request).to_owned();
+        let response = self.inner.send(id, request);
+        let cache = self.cache.clone();
+        Box::pin(async move {
+            let value = response.await?;
+            if let Some(key) = key {
+                if is_final(&method, &value) {
+                    let evicted = cache.lru.lock().expect("cache lock poisoned").insert(
+                        key,
+                        value.clone(),
+                        cache.capacity,
+                    );
+                    cache.evictions.fetch_add(evicted, Ordering::Relaxed);
+                }
+            }
+            Ok(value)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::tests::FlakyTransport as Flaky;
+    use serde_json::json;
+
+    fn ok(value: Value) -> Result<Value> {
+        Ok(value)
+    }
+
+    #[tokio::test]
+    async fn should_cache_queries_at_block_number() {
+        // given
+        let inner = Flaky::new(vec![ok(json!("0x1")), ok(json!("0x2"))]);
+        let transport = Cached::new(inner.clone(), 10);
+        let params = || vec![json!("sys"), json!("lax1"), json!("0x10")];
+
+        // when
+        let first = transport.execute("juice_getBalance", params()).await;
+        let second = transport.execute("juice_getBalance", params()).await;
+
+        // then
+        assert_eq!(first, ok(json!("0x1")));
+        assert_eq!(second, ok(json!("0x1")));
+        assert_eq!(inner.calls(), 1);
+        assert_eq!(
+            transport.stats(),
+            CacheStats {
+                hits: 1,
+                misses: 1,
+                evictions: 0,
+                entries: 1
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn should_not_cache_mutable_queries() {
+        let inner = Flaky::new(vec![ok(json!("0x1")), ok(json!("0x2"))]);
+        let transport = Cached::new(inner.clone(), 10);
+        let params = || vec![json!("sys"), json!("lax1"), json!("latest")];
+
+        transport
+            .execute("juice_getBalance", params())
+            .await
+            .unwrap();
+        let second = transport.execute("juice_getBalance", params()).await;
+
+        assert_eq!(second, ok(json!("0x2")));
+        assert_eq!(inner.calls(), 2);
+        assert_eq!(transport.stats().misses, 0);
+    }
+
+    #[tokio::test]
+    async fn should_not_cache_missing_or_pending_results() {
+        let pending = json!({"hash": "0x1", "blockHash": null});
+        let inner = Flaky::new(vec![ok(Value::Null), ok(pending.clone()), ok(pending)]);
+        let transport = Cached::new(inner.clone(), 10);
+        let params = || vec![json!("sys"), json!("0x1")];
+
+        for _ in 0..3 {
+            transport
+                .execute("juice_getTransactionByHash", params())
+                .await
+                .unwrap();
+        }
+
+        assert_eq!(inner.calls(), 3);
+        assert_eq!(transport.stats().entries, 0);
+    }
+
+    #[tokio::test]
+    async fn should_evict_least_recently_used() {
+        // given
+        let inner = Flaky::new(vec![ok(json!("a")), ok(json!("b")), ok(json!("c"))]);
+        let transport = Cached::new(inner.clone(), 2);
+        let params = |hash: &str| vec![json!("sys"), json!(hash)];
+
+        // when
+        transport
+            .execute("juice_getTransactionReceipt", params("0x1"))
+            .await
+            .unwrap();
+        transport
+            .execute("juice_getTransactionReceipt", params("0x2"))
+            .await
+            .unwrap();
+        transport
+            .execute("juice_getTransactionReceipt", params("0x1"))
+            .await
+            .unwrap();
+        transport
+            .execute("juice_getTransactionReceipt", params("0x3"))
+            .await
+            .unwrap();
+        let first = transport
+            .execute("juice_getTransactionReceipt", params("0x1"))
+            .await;
+
+        // then
+        assert_eq!(first, ok(json!("a")));
+        assert_eq!(inner.calls(), 3);
+        assert_eq!(transport.stats().evictions, 1);
+        assert_eq!(transport.stats().entries, 2);
+    }
+
+    #[tokio::test]
+    async fn should_persist_cache() {
+        // given
+        let path = std::env::temp_dir().join(format!("juice-cache-{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let params = || vec![json!("sys"), json!("0x1"), json!(false)];
+        let transport =
+            Cached::persistent(Flaky::new(vec![ok(json!({"number": "0x1"}))]), 10, &path).unwrap();
+        transport
+            .execute("juice_getBlockByHash", params())
+            .await
+            .unwrap();
+        transport.save().unwrap();
+
+        // when
+        let inner = Flaky::new(vec![]);
+        let transport = Cached::persistent(inner.clone(), 10, &path).unwrap();
+        let block = transport.execute("juice_getBlockByHash", params()).await;
+        fs::remove_file(&path).unwrap();
+
+        // then
+        assert_eq!(block, ok(json!({"number": "0x1"})));
+        assert_eq!(inner.calls(), 0);
+    }
+}
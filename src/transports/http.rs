@@ -4,13 +4,35 @@ use crate::{
 };
 use futures::future::BoxFuture;
 use jsonrpc_core::types::{Call, Output, Request, Value};
-use reqwest::{Client, Url};
+use reqwest::{
+    header::{HeaderMap, HeaderName, HeaderValue, AUTHORIZATION, RETRY_AFTER},
+    Certificate, Client, ClientBuilder, Identity, Proxy, RequestBuilder, Url,
+};
 use serde::de::DeserializeOwned;
-use std::sync::{
-    atomic::{AtomicUsize, Ordering},
-    Arc,
+use std::{
+    fmt,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
 };
 
+/// Provides headers computed for every request, e.g. short-lived tokens.
+pub trait HeaderProvider: Send + Sync {
+    /// Returns the headers to add to the next request.
+    fn headers(&self) -> Result<HeaderMap>;
+}
+
+impl<F> HeaderProvider for F
+where
+    F: Fn() -> Result<HeaderMap> + Send + Sync,
+{
+    fn headers(&self) -> Result<HeaderMap> {
+        self()
+    }
+}
+
 /// HTTP Transport
 #[derive(Debug, Clone)]
 pub struct Http {
@@ -18,30 +40,55 @@ pub struct Http {
     inner: Arc<Inner>,
 }
 
-#[derive(Debug)]
 struct Inner {
     url: Url,
     id: AtomicUsize,
+    headers: HeaderMap,
+    header_provider: Option<Box<dyn HeaderProvider>>,
+}
+
+impl fmt::Debug for Inner {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Inner")
+            .field("url", &self.url)
+            .field("id", &self.id)
+            .field("headers", &self.headers.keys().collect::<Vec<_>>())
+            .field("header_provider", &self.header_provider.is_some())
+            .finish()
+    }
 }
 
 impl Http {
     /// Create new HTTP transport connecting to given URL.
+    ///
+    /// Credentials embedded in the URL are sent using basic auth.
     pub fn new(url: &str) -> Result<Self> {
-        #[allow(unused_mut)]
-        let mut builder = Client::builder();
-        let client = builder
-            .build()
-            .map_err(|err| Error::Transport(format!("failed to build client: {}", err)))?;
-        Ok(Self::with_client(client, url.parse()?))
+        Self::builder(url).build()
+    }
+
+    /// Returns a builder to configure the transport connecting to given URL.
+    pub fn builder(url: &str) -> HttpBuilder {
+        HttpBuilder::new(url)
     }
 
     /// Like `new` but with a user provided client instance.
     pub fn with_client(client: Client, url: Url) -> Self {
+        Self::with_inner(client, url, HeaderMap::new(), None)
+    }
+
+    fn with_inner(
+        client: Client,
+        url: Url,
+        headers: HeaderMap,
+        header_provider: Option<Box<dyn HeaderProvider>>,
+    ) -> Self {
         Self {
             client,
             inner: Arc::new(Inner {
                 url,
                 id: AtomicUsize::new(0),
+                headers,
+                header_provider,
             }),
         }
     }
@@ -50,14 +97,207 @@ impl Http {
         self.inner.id.fetch_add(1, Ordering::AcqRel)
     }
 
-    fn new_request(&self) -> (Client, Url) {
-        (self.client.clone(), self.inner.url.clone())
+    /// Credentials embedded in the URL are turned into an `Authorization`
+    /// header by reqwest, the configured headers replace it.
+    fn new_request(&self) -> Result<RequestBuilder> {
+        let mut request = self
+            .client
+            .post(self.inner.url.clone())
+            .headers(self.inner.headers.clone());
+        if let Some(ref provider) = self.inner.header_provider {
+            request = request.headers(provider.headers()?);
+        }
+        Ok(request)
+    }
+}
+
+/// Builder of a configured HTTP transport.
+pub struct HttpBuilder {
+    url: String,
+    client: ClientBuilder,
+    headers: HeaderMap,
+    header_provider: Option<Box<dyn HeaderProvider>>,
+    error: Option<Error>,
+}
+
+impl fmt::Debug for HttpBuilder {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("HttpBuilder")
+            .field("url", &self.url)
+            .field("client", &self.client)
+            .field("headers", &self.headers.keys().collect::<Vec<_>>())
+            .field("header_provider", &self.header_provider.is_some())
+            .field("error", &self.error)
+            .finish()
+    }
+}
+
+impl HttpBuilder {
+    /// Create a new builder for the transport connecting to given URL.
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.into(),
+            client: Client::builder(),
+            headers: HeaderMap::new(),
+            header_provider: None,
+            error: None,
+        }
+    }
+
+    /// Set the timeout of the connect phase.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.client = self.client.connect_timeout(timeout);
+        self
+    }
+
+    /// Set the timeout of whole requests, from connecting until the response
+    /// body has been read.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.client = self.client.timeout(timeout);
+        self
+    }
+
+    /// Add a header sent with every request, e.g. an API key.
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        match (
+            HeaderName::from_bytes(name.as_bytes()),
+            HeaderValue::from_str(value),
+        ) {
+            (Ok(name), Ok(value)) => {
+                self.headers.insert(name, value);
+            }
+            _ => self.fail(format!("invalid header: {}", name)),
+        }
+        self
+    }
+
+    /// Authenticate every request with the given bearer token.
+    ///
+    /// Replaces the authentication set before, and the credentials embedded
+    /// in the URL.
+    pub fn bearer_auth(self, token: &str) -> Self {
+        self.authorization(format!("Bearer {}", token), "invalid bearer token")
+    }
+
+    /// Authenticate every request with basic auth.
+    ///
+    /// Replaces the authentication set before, and the credentials embedded
+    /// in the URL.
+    pub fn basic_auth(self, user: &str, password: Option<&str>) -> Self {
+        use base64::Engine;
+
+        let credentials = format!("{}:{}", user, password.unwrap_or_default());
+        let encoded = base64::engine::general_purpose::STANDARD.encode(credentials);
+        self.authorization(format!("Basic {}", encoded), "invalid basic auth user")
+    }
+
+    fn authorization(mut self, value: String, error: &str) -> Self {
+        match HeaderValue::from_str(&value) {
+            Ok(mut value) => {
+                value.set_sensitive(true);
+                self.headers.insert(AUTHORIZATION, value);
+            }
+            Err(_) => self.fail(error.into()),
+        }
+        self
+    }
+
+    /// Add headers computed for every request.
+    pub fn header_provider<P: HeaderProvider + 'static>(mut self, provider: P) -> Self {
+        self.header_provider = Some(Box::new(provider));
+        self
+    }
+
+    /// Trust the given root certificate, e.g. the CA of a private gateway.
+    pub fn add_root_certificate(mut self, cert: Certificate) -> Self {
+        self.client = self.client.add_root_certificate(cert);
+        self
+    }
+
+    /// Trust the PEM encoded root certificate.
+    pub fn add_root_certificate_pem(self, pem: &[u8]) -> Self {
+        match Certificate::from_pem(pem) {
+            Ok(cert) => self.add_root_certificate(cert),
+            Err(err) => self.failed(format!("invalid root certificate: {}", err)),
+        }
+    }
+
+    /// Use the given client certificate, for mutual TLS.
+    pub fn identity(mut self, identity: Identity) -> Self {
+        self.client = self.client.identity(identity);
+        self
+    }
+
+    /// Use the PEM encoded client certificate chain and PKCS #8 private key,
+    /// for mutual TLS.
+    pub fn identity_pem(self, cert: &[u8], key: &[u8]) -> Self {
+        match Identity::from_pkcs8_pem(cert, key) {
+            Ok(identity) => self.identity(identity),
+            Err(err) => self.failed(format!("invalid client certificate: {}", err)),
+        }
+    }
+
+    /// Send requests through the given proxy.
+    pub fn proxy(mut self, proxy: Proxy) -> Self {
+        self.client = self.client.proxy(proxy);
+        self
+    }
+
+    /// Don't use any proxy, including the system ones.
+    pub fn no_proxy(mut self) -> Self {
+        self.client = self.client.no_proxy();
+        self
+    }
+
+    /// Enable gzip decompression of responses.
+    pub fn gzip(mut self, enable: bool) -> Self {
+        self.client = self.client.gzip(enable);
+        self
+    }
+
+    /// Build the transport.
+    pub fn build(self) -> Result<Http> {
+        if let Some(err) = self.error {
+            return Err(err);
+        }
+        let client = self
+            .client
+            .build()
+            .map_err(|err| Error::Transport(format!("failed to build client: {}", err)))?;
+        Ok(Http::with_inner(
+            client,
+            self.url.parse()?,
+            self.headers,
+            self.header_provider,
+        ))
+    }
+
+    fn fail(&mut self, message: String) {
+        if self.error.is_none() {
+            self.error = Some(Error::Transport(message));
+        }
+    }
+
+    fn failed(mut self, message: String) -> Self {
+        self.fail(message);
+        self
     }
 }
 
+/// Parse the `Retry-After` header, given either in seconds or as a date.
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    if let Ok(secs) = value.trim().parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let date = httpdate::parse_http_date(value).ok()?;
+    Some(
+        date.duration_since(std::time::SystemTime::now())
+            .unwrap_or_default(),
+    )
+}
+
 async fn execute_rpc<T: DeserializeOwned>(
-    client: &Client,
-    url: Url,
+    request_builder: RequestBuilder,
     request: &Request,
     id: RequestId,
 ) -> Result<T> {
@@ -66,30 +306,44 @@ async fn execute_rpc<T: DeserializeOwned>(
         id,
         serde_json::to_string(&request)?
     );
-    let response = client
-        .post(url)
+    let response = request_builder
         .json(request)
         .send()
         .await
-        .map_err(|err| Error::Transport(format!("failed to send request: {}", err)))?;
+        .map_err(|err| request_error("failed to send request", err))?;
     let status = response.status();
+    let retry_after = response
+        .headers()
+        .get(RETRY_AFTER)
+        .and_then(|value| value.to_str().ok())
+        .and_then(parse_retry_after);
     let response = response
         .bytes()
         .await
-        .map_err(|err| Error::Transport(format!("failed to read response bytes: {}", err)))?;
+        .map_err(|err| request_error("failed to read response bytes", err))?;
     log::debug!(
         "[id:{}] received response: {:?}",
         id,
         String::from_utf8_lossy(&response).as_ref()
     );
     if !status.is_success() {
-        return Err(Error::Transport(format!(
-            "response status code is not success: {}",
-            status,
-        )));
+        return Err(Error::HttpStatus {
+            status: status.as_u16(),
+            retry_after,
+        });
     }
     helpers::arbitrary_precision_deserialize_workaround(&response)
-        .map_err(|err| Error::Transport(format!("failed to deserialize response: {}", err)))
+        .map_err(|err| Error::Decoder(format!("failed to deserialize response: {}", err)))
+}
+
+fn request_error(context: &str, err: reqwest::Error) -> Error {
+    if err.is_timeout() {
+        Error::Timeout
+    } else if err.is_connect() {
+        Error::Connection(format!("{}: {}", context, err))
+    } else {
+        Error::Transport(format!("{}: {}", context, err))
+    }
 }
 
 impl Transport for Http {
@@ -102,9 +356,9 @@ impl Transport for Http {
     }
 
     fn send(&self, id: RequestId, call: Call) -> Self::Out {
-        let (client, url) = self.new_request();
+        let request = self.new_request();
         Box::pin(async move {
-            let output: Output = execute_rpc(&client, url, &Request::Single(call), id).await?;
+            let output: Output = execute_rpc(request?, &Request::Single(call), id).await?;
             helpers::to_result_from_output(output)
         })
     }
@@ -129,7 +383,7 @@ mod tests {
         while let Some(Ok(chunk)) = body.data().await {
             content.extend(&*chunk);
         }
-        assert_eq!(std::str::from_utf8(&*content), Ok(expected));
+        assert_eq!(std::str::from_utf8(&content), Ok(expected));
 
         Ok(hyper::Response::new(response.into()))
     }
@@ -157,4 +411,151 @@ mod tests {
         // then
         assert_eq!(response, Ok(Value::String("x".into())));
     }
+
+    async fn start_server<F>(handler: F) -> std::net::SocketAddr
+    where
+        F: Fn(hyper::Request<hyper::Body>) -> hyper::Response<hyper::Body>
+            + Clone
+            + Send
+            + Sync
+            + 'static,
+    {
+        use hyper::service::{make_service_fn, service_fn};
+
+        let service = make_service_fn(move |_| {
+            let handler = handler.clone();
+            async move {
+                Ok::<_, hyper::Error>(service_fn(move |req| {
+                    let response = handler(req);
+                    async move { Ok::<_, hyper::Error>(response) }
+                }))
+            }
+        });
+        let server = hyper::Server::bind(&"127.0.0.1:0".parse().unwrap()).serve(service);
+        let addr = server.local_addr();
+        tokio::spawn(server);
+        addr
+    }
+
+    fn echo_headers(req: hyper::Request<hyper::Body>) -> hyper::Response<hyper::Body> {
+        let header = |name: &str| {
+            req.headers()
+                .get(name)
+                .map(|value| value.to_str().unwrap().to_owned())
+        };
+        let result = serde_json::json!({
+            "authorization": header("authorization"),
+            "authorizations": req.headers().get_all("authorization").iter().count(),
+            "key": header("x-api-key"),
+            "nonce": header("x-nonce"),
+        });
+        let response = serde_json::json!({"jsonrpc": "2.0", "id": 0, "result": result});
+        hyper::Response::new(response.to_string().into())
+    }
+
+    #[tokio::test]
+    async fn should_send_configured_headers() {
+        // given
+        let addr = start_server(echo_headers).await;
+        let client = Http::builder(&format!("http://{}", addr))
+            .timeout(std::time::Duration::from_secs(5))
+            .bearer_auth("token")
+            .header("x-api-key", "key")
+            .header_provider(|| {
+                let mut headers = HeaderMap::new();
+                headers.insert("x-nonce", HeaderValue::from_static("1"));
+                Ok(headers)
+            })
+            .build()
+            .unwrap();
+
+        // when
+        let response = client.execute("juice_blockNumber", vec![]).await;
+
+        // then
+        assert_eq!(
+            response,
+            Ok(serde_json::json!({
+                "authorization": "Bearer token",
+                "authorizations": 1,
+                "key": "key",
+                "nonce": "1",
+            }))
+        );
+    }
+
+    #[tokio::test]
+    async fn should_use_credentials_from_url() {
+        let addr = start_server(echo_headers).await;
+        let client = Http::new(&format!("http://user:p%40ss@{}", addr)).unwrap();
+
+        let response = client.execute("juice_blockNumber", vec![]).await;
+
+        let response = response.unwrap();
+        assert_eq!(response["authorization"], "Basic dXNlcjpwQHNz");
+        assert_eq!(response["authorizations"], 1);
+    }
+
+    #[tokio::test]
+    async fn should_send_a_single_authorization() {
+        // given
+        let addr = start_server(echo_headers).await;
+        let url = format!("http://user:pass@{}", addr);
+        let bearer_then_basic = Http::builder(&url)
+            .bearer_auth("token")
+            .basic_auth("other", Some("secret"))
+            .build()
+            .unwrap();
+        let basic_then_bearer = Http::builder(&url)
+            .basic_auth("other", Some("secret"))
+            .bearer_auth("token")
+            .build()
+            .unwrap();
+
+        // when
+        let first = bearer_then_basic.execute("juice_blockNumber", vec![]);
+        let second = basic_then_bearer.execute("juice_blockNumber", vec![]);
+        let (first, second) = (first.await.unwrap(), second.await.unwrap());
+
+        // then
+        assert_eq!(first["authorization"], "Basic b3RoZXI6c2VjcmV0");
+        assert_eq!(first["authorizations"], 1);
+        assert_eq!(second["authorization"], "Bearer token");
+        assert_eq!(second["authorizations"], 1);
+    }
+
+    #[tokio::test]
+    async fn should_return_http_status() {
+        let addr = start_server(|_| {
+            hyper::Response::builder()
+                .status(429)
+                .header("retry-after", "3")
+                .body(hyper::Body::empty())
+                .unwrap()
+        })
+        .await;
+        let client = Http::new(&format!("http://{}", addr)).unwrap();
+
+        let response = client.execute("juice_blockNumber", vec![]).await;
+
+        assert_eq!(
+            response,
+            Err(Error::HttpStatus {
+                status: 429,
+                retry_after: Some(std::time::Duration::from_secs(3)),
+            })
+        );
+    }
+
+    #[test]
+    fn should_reject_invalid_header() {
+        let result = Http::builder("http://127.0.0.1:1")
+            .header("x-api-key", "bad\nvalue")
+            .build();
+
+        assert_eq!(
+            result.unwrap_err(),
+            Error::Transport("invalid header: x-api-key".into())
+        );
+    }
 }
@@ -0,0 +1,334 @@
+use crate::{
+    error::{Error, Result},
+    helpers, RequestId, Transport,
+};
+use futures::future::BoxFuture;
+use jsonrpc_core::types::{Call, Params, Value};
+use std::{
+    collections::BTreeMap,
+    fmt,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tracing::Instrument;
+
+/// Upper bounds of the latency histogram buckets used by `InMemoryMetrics`.
+pub const DEFAULT_LATENCY_BUCKETS: [Duration; 11] = [
+    Duration::from_millis(5),
+    Duration::from_millis(10),
+    Duration::from_millis(25),
+    Duration::from_millis(50),
+    Duration::from_millis(100),
+    Duration::from_millis(250),
+    Duration::from_millis(500),
+    Duration::from_secs(1),
+    Duration::from_millis(2500),
+    Duration::from_secs(5),
+    Duration::from_secs(10),
+];
+
+/// Receives the measurements of every request sent by `Instrumented`.
+///
+/// Implement it to forward the measurements to a metrics system, such as a
+/// Prometheus registry.
+pub trait Metrics: fmt::Debug + Send + Sync {
+    /// Called when a request of the given method is sent.
+    fn request_started(&self, method: &str);
+
+    /// Called when the response of a request of the given method is
+    /// received, `error` is set if the request failed.
+    fn request_finished(&self, method: &str, latency: Duration, error: Option<&Error>);
+
+    /// Called when a request of the given method is dropped before its
+    /// response was received.
+    fn request_cancelled(&self, method: &str, latency: Duration);
+}
+
+/// Latency histogram.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Histogram {
+    /// Upper bounds of the buckets
+    pub bounds: Vec<Duration>,
+    /// Number of samples in every bucket, the last one counts the samples
+    /// above the highest bound
+    pub counts: Vec<u64>,
+    /// Sum of all the samples
+    pub sum: Duration,
+}
+
+impl Histogram {
+    fn new(bounds: &[Duration]) -> Self {
+        Histogram {
+            bounds: bounds.to_vec(),
+            counts: vec![0; bounds.len() + 1],
+            sum: Duration::default(),
+        }
+    }
+
+    fn observe(&mut self, latency: Duration) {
+        let idx = self.bounds.partition_point(|bound| *bound < latency);
+        self.counts[idx] += 1;
+        self.sum += latency;
+    }
+
+    /// Returns the number of samples.
+    pub fn count(&self) -> u64 {
+        self.counts.iter().sum()
+    }
+}
+
+/// Measurements of a single method.
+#[derive(Debug, Clone, PartialEq)]
+pub struct MethodMetrics {
+    /// Number of requests waiting for a response
+    pub in_flight: u64,
+    /// Latency of the finished requests
+    pub latency: Histogram,
+    /// Number of failed requests by error kind, see `Error::kind`
+    pub errors: BTreeMap<&'static str, u64>,
+    /// Number of requests dropped before their response was received
+    pub cancelled: u64,
+}
+
+/// Metrics kept in memory, per method.
+#[derive(Debug)]
+pub struct InMemoryMetrics {
+    bounds: Vec<Duration>,
+    methods: Mutex<BTreeMap<String, MethodMetrics>>,
+}
+
+impl Default for InMemoryMetrics {
+    fn default() -> Self {
+        Self::new(&DEFAULT_LATENCY_BUCKETS)
+    }
+}
+
+impl InMemoryMetrics {
+    /// Create new metrics with the given latency bucket bounds.
+    pub fn new(bounds: &[Duration]) -> Self {
+        let mut bounds = bounds.to_vec();
+        bounds.sort();
+        InMemoryMetrics {
+            bounds,
+            methods: Default::default(),
+        }
+    }
+
+    /// Returns the measurements of every method.
+    pub fn snapshot(&self) -> BTreeMap<String, MethodMetrics> {
+        self.methods.lock().expect("metrics lock poisoned").clone()
+    }
+
+    fn update(&self, method: &str, update: impl FnOnce(&mut MethodMetrics)) {
+        let mut methods = self.methods.lock().expect("metrics lock poisoned");
+        if !methods.contains_key(method) {
+            let metrics = MethodMetrics {
+                in_flight: 0,
+                latency: Histogram::new(&self.bounds),
+                errors: BTreeMap::new(),
+                cancelled: 0,
+            };
+            methods.insert(method.into(), metrics);
+        }
+        update(methods.get_mut(method).expect("inserted above"));
+    }
+}
+
+impl Metrics for InMemoryMetrics {
+    fn request_started(&self, method: &str) {
+        self.update(method, |metrics| metrics.in_flight += 1);
+    }
+
+    fn request_finished(&self, method: &str, latency: Duration, error: Option<&Error>) {
+        self.update(method, |metrics| {
+            metrics.in_flight = metrics.in_flight.saturating_sub(1);
+            metrics.latency.observe(latency);
+            if let Some(error) = error {
+                *metrics.errors.entry(error.kind()).or_default() += 1;
+            }
+        });
+    }
+
+    /// Cancelled requests are only counted, they are not part of the
+    /// latency histogram.
+    fn request_cancelled(&self, method: &str, _latency: Duration) {
+        self.update(method, |metrics| {
+            metrics.in_flight = metrics.in_flight.saturating_sub(1);
+            metrics.cancelled += 1;
+        });
+    }
+}
+
+/// Reports a request as cancelled if it is dropped before `finish` is called.
+struct InFlight {
+    method: String,
+    metrics: Arc<dyn Metrics>,
+    started: Instant,
+    finished: bool,
+}
+
+impl InFlight {
+    fn new(method: String, metrics: Arc<dyn Metrics>) -> Self {
+        metrics.request_started(&method);
+        InFlight {
+            method,
+            metrics,
+            started: Instant::now(),
+            finished: false,
+        }
+    }
+
+    fn finish(mut self, error: Option<&Error>) -> Duration {
+        let latency = self.started.elapsed();
+        self.finished = true;
+        self.metrics.request_finished(&self.method, latency, error);
+        latency
+    }
+}
+
+impl Drop for InFlight {
+    fn drop(&mut self) {
+        if !self.finished {
+            let latency = self.started.elapsed();
+            self.metrics.request_cancelled(&self.method, latency);
+            tracing::debug!(?latency, "request cancelled");
+        }
+    }
+}
+
+/// Transport wrapper measuring every request and tracing it.
+///
+/// Every request runs in a `juice_rpc` span carrying the method, the ledger
+/// and the request id, so slow calls can be correlated in traces.
+#[derive(Debug, Clone)]
+pub struct Instrumented<T> {
+    inner: T,
+    metrics: Arc<dyn Metrics>,
+}
+
+impl<T: Transport> Instrumented<T> {
+    /// Create a new transport reporting to the given metrics.
+    pub fn new(inner: T, metrics: Arc<dyn Metrics>) -> Self {
+        Self { inner, metrics }
+    }
+
+    /// Returns the wrapped transport.
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+}
+
+impl<T> Transport for Instrumented<T>
+where
+    T: Transport + Send + Sync + 'static,
+    T::Out: Send,
+{
+    type Out = BoxFuture<'static, Result<Value>>;
+
+    fn prepare(&self, method: &str, params: Vec<Value>) -> (RequestId, Call) {
+        self.inner.prepare(method, params)
+    }
+
+    fn send(&self, id: RequestId, request: Call) -> Self::Out {
+        let method = helpers::call_method(&request).to_owned();
+        // The ledger is the first parameter of the `juice_*` methods.
+        let ledger = match request {
+            Call::MethodCall(ref call) => match call.params {
+                Params::Array(ref params) => params.first().and_then(Value::as_str),
+                _ => None,
+            },
+            _ => None,
+        };
+        let span = tracing::info_span!("juice_rpc", method = %method, ledger, id);
+
+        let in_flight = InFlight::new(method, self.metrics.clone());
+        let response = self.inner.send(id, request);
+        Box::pin(
+            async move {
+                let result = response.await;
+                let latency = in_flight.finish(result.as_ref().err());
+                match result {
+                    Ok(_) => tracing::debug!(?latency, "request succeeded"),
+                    Err(ref err) => tracing::debug!(?latency, error = %err, "request failed"),
+                }
+                result
+            }
+            .instrument(span),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::tests::FlakyTransport as Flaky;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn should_record_metrics() {
+        // given
+        let metrics = Arc::new(InMemoryMetrics::default());
+        let inner = Flaky::new(vec![
+            Ok(json!("0x1")),
+            Err(Error::Rpc(jsonrpc_core::Error::invalid_params("bad"))),
+            Ok(json!("0x2")),
+        ]);
+        let transport = Instrumented::new(inner, metrics.clone());
+
+        // when
+        transport
+            .execute("juice_getBalance", vec![json!("sys")])
+            .await
+            .unwrap();
+        transport
+            .execute("juice_getBalance", vec![json!("sys")])
+            .await
+            .unwrap_err();
+        let pending = transport.execute("juice_blockNumber", vec![json!("sys")]);
+        let in_flight = metrics.snapshot()["juice_blockNumber"].in_flight;
+        pending.await.unwrap();
+
+        // then
+        let snapshot = metrics.snapshot();
+        let balance = &snapshot["juice_getBalance"];
+        assert_eq!(in_flight, 1);
+        assert_eq!(balance.in_flight, 0);
+        assert_eq!(balance.latency.count(), 2);
+        assert_eq!(balance.errors.get("rpc"), Some(&1));
+        assert_eq!(snapshot["juice_blockNumber"].in_flight, 0);
+        assert!(snapshot["juice_blockNumber"].errors.is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_record_cancelled_requests() {
+        // given
+        let metrics = Arc::new(InMemoryMetrics::default());
+        let transport = Instrumented::new(Flaky::new(vec![]), metrics.clone());
+
+        // when
+        let pending = transport.execute("juice_blockNumber", vec![json!("sys")]);
+        let in_flight = metrics.snapshot()["juice_blockNumber"].in_flight;
+        drop(pending);
+
+        // then
+        let snapshot = &metrics.snapshot()["juice_blockNumber"];
+        assert_eq!(in_flight, 1);
+        assert_eq!(snapshot.in_flight, 0);
+        assert_eq!(snapshot.cancelled, 1);
+        assert_eq!(snapshot.latency.count(), 0);
+        assert!(snapshot.errors.is_empty());
+    }
+
+    #[test]
+    fn should_bucket_latencies() {
+        let mut histogram =
+            Histogram::new(&[Duration::from_millis(10), Duration::from_millis(100)]);
+
+        histogram.observe(Duration::from_millis(10));
+        histogram.observe(Duration::from_millis(50));
+        histogram.observe(Duration::from_secs(1));
+
+        assert_eq!(histogram.counts, vec![1, 1, 1]);
+        assert_eq!(histogram.sum, Duration::from_millis(1060));
+    }
+}
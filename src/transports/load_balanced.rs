@@ -0,0 +1,326 @@
+use crate::{
+    error::{Error, Result},
+    helpers,
+    transports::retry,
+    RequestId, Transport,
+};
+use futures::future::{self, BoxFuture};
+use jsonrpc_core::types::{Call, Value};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+/// Default number of blocks an endpoint may lag behind the best one.
+pub const DEFAULT_MAX_LAG: u64 = 10;
+
+/// Weight of the latest sample in the latency moving average.
+const LATENCY_WEIGHT: f64 = 0.2;
+
+/// How requests are spread over the endpoints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Strategy {
+    /// Rotate over the healthy endpoints.
+    RoundRobin,
+    /// Always use the first healthy endpoint, the others are fallbacks.
+    Fallback,
+    /// Prefer the healthy endpoint with the lowest average latency.
+    LowestLatency,
+}
+
+/// Statistics of a single endpoint.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct EndpointStats {
+    /// Number of requests sent
+    pub requests: u64,
+    /// Number of requests failed because of the transport
+    pub failures: u64,
+    /// Moving average of the request latency
+    pub latency: Option<Duration>,
+    /// Block number seen by the last health check
+    pub block_number: Option<u64>,
+    /// Whether the endpoint is used for requests
+    pub healthy: bool,
+}
+
+#[derive(Debug)]
+struct Endpoint<T> {
+    transport: T,
+    stats: Mutex<EndpointStats>,
+}
+
+impl<T> Endpoint<T> {
+    fn stats(&self) -> EndpointStats {
+        self.stats.lock().expect("stats lock poisoned").clone()
+    }
+
+    fn record(&self, latency: Duration, failed: bool) {
+        let mut stats = self.stats.lock().expect("stats lock poisoned");
+        stats.requests += 1;
+        if failed {
+            stats.failures += 1;
+            return;
+        }
+        stats.latency = Some(match stats.latency {
+            Some(avg) => avg.mul_f64(1.0 - LATENCY_WEIGHT) + latency.mul_f64(LATENCY_WEIGHT),
+            None => latency,
+        });
+    }
+}
+
+/// Transport spreading requests over several nodes of the same ledgers.
+///
+/// Read-only requests failing because of the transport are sent to the next
+/// endpoint. Other requests are only sent to the next endpoint if the
+/// connection failed before they were sent, so that a transaction is not
+/// submitted twice. Errors returned by a node are passed through. Endpoints which are not
+/// healthy are skipped, unless no healthy endpoint is left.
+#[derive(Debug, Clone)]
+pub struct LoadBalanced<T> {
+    endpoints: Arc<Vec<Endpoint<T>>>,
+    strategy: Strategy,
+    next: Arc<AtomicUsize>,
+    max_lag: u64,
+}
+
+impl<T: Transport> LoadBalanced<T> {
+    /// Create a new transport over the given endpoints.
+    ///
+    /// Panics if no endpoint is given.
+    pub fn new(transports: Vec<T>, strategy: Strategy) -> Self {
+        assert!(!transports.is_empty(), "at least one endpoint is required");
+        let endpoints = transports
+            .into_iter()
+            .map(|transport| Endpoint {
+                transport,
+                stats: Mutex::new(EndpointStats {
+                    healthy: true,
+                    ..Default::default()
+                }),
+            })
+            .collect();
+        Self {
+            endpoints: Arc::new(endpoints),
+            strategy,
+            next: Default::default(),
+            max_lag: DEFAULT_MAX_LAG,
+        }
+    }
+
+    /// Set the number of blocks an endpoint may lag behind the best one
+    /// before it is ejected by the health check.
+    pub fn max_lag(mut self, max_lag: u64) -> Self {
+        self.max_lag = max_lag;
+        self
+    }
+
+    /// Returns the statistics of every endpoint, in the order given to `new`.
+    pub fn stats(&self) -> Vec<EndpointStats> {
+        self.endpoints.iter().map(Endpoint::stats).collect()
+    }
+
+    /// Returns the endpoint indexes in the order they should be tried.
+    fn order(&self) -> Vec<usize> {
+        let stats = self.stats();
+        let mut healthy = (0..stats.len())
+            .filter(|idx| stats[*idx].healthy)
+            .collect::<Vec<_>>();
+        if healthy.is_empty() {
+            healthy = (0..stats.len()).collect();
+        }
+        match self.strategy {
+            Strategy::Fallback => {}
+            Strategy::RoundRobin => {
+                let start = self.next.fetch_add(1, Ordering::AcqRel) % healthy.len();
+                healthy.rotate_left(start);
+            }
+            Strategy::LowestLatency => {
+                healthy.sort_by_key(|idx| stats[*idx].latency.unwrap_or_default());
+            }
+        }
+        healthy
+    }
+
+    /// Checks the block number of every endpoint on the given ledger.
+    ///
+    /// Endpoints which fail to answer, or lag more than `max_lag` blocks
+    /// behind the best one, are marked unhealthy until the next check.
+    pub async fn health_check(&self, ledger: &str) {
+        let ledger = helpers::serialize(&ledger);
+        let numbers = future::join_all(self.endpoints.iter().map(|endpoint| {
+            let ledger = ledger.clone();
+            async move {
+                let number = endpoint
+                    .transport
+                    .execute("juice_blockNumber", vec![ledger])
+                    .await
+                    .and_then(helpers::decode::<crate::types::U64>);
+                number.ok().map(|number| number.as_u64())
+            }
+        }))
+        .await;
+
+        let best = numbers.iter().flatten().max().copied().unwrap_or_default();
+        for (endpoint, number) in self.endpoints.iter().zip(numbers) {
+            let mut stats = endpoint.stats.lock().expect("stats lock poisoned");
+            stats.healthy = matches!(number, Some(number) if best - number <= self.max_lag);
+            stats.block_number = number;
+        }
+    }
+}
+
+impl<T> Transport for LoadBalanced<T>
+where
+    T: Transport + Send + Sync + 'static,
+    T::Out: Send,
+{
+    type Out = BoxFuture<'static, Result<Value>>;
+
+    fn prepare(&self, method: &str, params: Vec<Value>) -> (RequestId, Call) {
+        self.endpoints[0].transport.prepare(method, params)
+    }
+
+    fn send(&self, id: RequestId, request: Call) -> Self::Out {
+        let this = self.clone();
+        let read_only = match request {
+            Call::MethodCall(ref call) => retry::is_read_only(&call.method),
+            _ => false,
+        };
+        Box::pin(async move {
+            let mut last_err = Error::Unreachable;
+            for idx in this.order() {
+                let endpoint = &this.endpoints[idx];
+                let started = Instant::now();
+                let result = endpoint.transport.send(id, request.clone()).await;
+                let failed = matches!(result, Err(ref err) if err.is_transport());
+                endpoint.record(started.elapsed(), failed);
+                match result {
+                    Err(err) if failed && (read_only || matches!(err, Error::Connection(_))) => {
+                        log::debug!("[id:{}] endpoint {} failed: {}", id, idx, err);
+                        last_err = err;
+                    }
+                    result => return result,
+                }
+            }
+            Err(last_err)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::tests::FlakyTransport as Flaky;
+
+    fn transport_error() -> Error {
+        Error::Transport("connection refused".into())
+    }
+
+    fn ok(value: &str) -> Result<Value> {
+        Ok(Value::String(value.into()))
+    }
+
+    #[tokio::test]
+    async fn should_fall_back_to_next_endpoint() {
+        // given
+        let primary = Flaky::new(vec![Err(transport_error()), ok("0x2")]);
+        let secondary = Flaky::new(vec![ok("0x1")]);
+        let transport =
+            LoadBalanced::new(vec![primary.clone(), secondary.clone()], Strategy::Fallback);
+
+        // when
+        let first = transport.execute("juice_getBalance", vec![]).await;
+        let second = transport.execute("juice_getBalance", vec![]).await;
+
+        // then
+        assert_eq!(first, ok("0x1"));
+        assert_eq!(second, ok("0x2"));
+        assert_eq!(primary.calls(), 2);
+        assert_eq!(secondary.calls(), 1);
+        let stats = transport.stats();
+        assert_eq!((stats[0].requests, stats[0].failures), (2, 1));
+        assert_eq!((stats[1].requests, stats[1].failures), (1, 0));
+    }
+
+    #[tokio::test]
+    async fn should_not_fall_back_on_node_errors() {
+        let primary = Flaky::new(vec![Err(Error::Rpc(jsonrpc_core::Error::invalid_params(
+            "bad",
+        )))]);
+        let secondary = Flaky::new(vec![ok("0x1")]);
+        let transport = LoadBalanced::new(vec![primary, secondary.clone()], Strategy::Fallback);
+
+        let result = transport.execute("juice_getBalance", vec![]).await;
+
+        assert!(matches!(result, Err(Error::Rpc(_))));
+        assert_eq!(secondary.calls(), 0);
+    }
+
+    #[tokio::test]
+    async fn should_not_resend_writes_after_timeout() {
+        // given
+        let primary = Flaky::new(vec![Err(Error::Timeout)]);
+        let secondary = Flaky::new(vec![ok("0x1")]);
+        let transport = LoadBalanced::new(vec![primary, secondary.clone()], Strategy::Fallback);
+
+        // when
+        let result = transport.execute("juice_sendRawTransaction", vec![]).await;
+
+        // then
+        assert_eq!(result, Err(Error::Timeout));
+        assert_eq!(secondary.calls(), 0);
+    }
+
+    #[tokio::test]
+    async fn should_fall_back_on_connection_failure_for_writes() {
+        let primary = Flaky::new(vec![Err(Error::Connection("refused".into()))]);
+        let secondary = Flaky::new(vec![ok("0x1")]);
+        let transport = LoadBalanced::new(vec![primary, secondary.clone()], Strategy::Fallback);
+
+        let result = transport.execute("juice_sendRawTransaction", vec![]).await;
+
+        assert_eq!(result, ok("0x1"));
+        assert_eq!(secondary.calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn should_rotate_endpoints() {
+        let a = Flaky::new(vec![ok("a"), ok("a")]);
+        let b = Flaky::new(vec![ok("b"), ok("b")]);
+        let transport = LoadBalanced::new(vec![a, b], Strategy::RoundRobin);
+
+        let mut results = vec![];
+        for _ in 0..4 {
+            results.push(transport.execute("juice_getBalance", vec![]).await);
+        }
+
+        assert_eq!(results, vec![ok("a"), ok("b"), ok("a"), ok("b")]);
+    }
+
+    #[tokio::test]
+    async fn should_eject_lagging_endpoints() {
+        // given
+        let a = Flaky::new(vec![ok("0x64"), ok("a")]);
+        let b = Flaky::new(vec![ok("0x50")]);
+        let c = Flaky::new(vec![Err(transport_error())]);
+        let transport = LoadBalanced::new(vec![b.clone(), a, c], Strategy::Fallback).max_lag(5);
+
+        // when
+        transport.health_check("sys").await;
+        let result = transport.execute("juice_getBalance", vec![]).await;
+
+        // then
+        let stats = transport.stats();
+        assert_eq!(
+            stats.iter().map(|s| s.healthy).collect::<Vec<_>>(),
+            vec![false, true, false]
+        );
+        assert_eq!(stats[0].block_number, Some(0x50));
+        assert_eq!(stats[2].block_number, None);
+        assert_eq!(result, ok("a"));
+        assert_eq!(b.methods(), vec!["juice_blockNumber"]);
+    }
+}
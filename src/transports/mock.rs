@@ -0,0 +1,329 @@
+//! Mock transport for tests of code built on top of the client.
+//!
+//! Available with the `test-utils` feature.
+
+use crate::{
+    error::{Error, Result},
+    helpers, RequestId, Transport,
+};
+use futures::future;
+use jsonrpc_core::types::{Call, Params, Value};
+use std::{
+    fmt,
+    sync::{Arc, Mutex},
+};
+
+type Responder = Arc<dyn Fn(&[Value]) -> Result<Value> + Send + Sync>;
+
+struct Expectation {
+    method: String,
+    params: Option<Vec<Value>>,
+    times: Option<usize>,
+    calls: usize,
+    responder: Responder,
+}
+
+impl Expectation {
+    fn matches(&self, method: &str, params: &[Value]) -> bool {
+        let params_match = match self.params {
+            Some(ref expected) => expected == params,
+            None => true,
+        };
+        let calls_left = match self.times {
+            Some(times) => self.calls < times,
+            None => true,
+        };
+        self.method == method && params_match && calls_left
+    }
+}
+
+impl fmt::Debug for Expectation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Expectation")
+            .field("method", &self.method)
+            .field("params", &self.params)
+            .field("times", &self.times)
+            .field("calls", &self.calls)
+            .finish()
+    }
+}
+
+#[derive(Debug, Default)]
+struct State {
+    id: RequestId,
+    expectations: Vec<Expectation>,
+    calls: Vec<(String, Vec<Value>)>,
+    unexpected: Vec<(String, Vec<Value>)>,
+}
+
+/// Thread-safe transport answering requests from programmed expectations.
+///
+/// A request is answered by the first expectation matching its method and,
+/// if given, its params, which was not already called the expected number of
+/// times. Requests matching no expectation fail with `Error::Unreachable` and
+/// make `verify` panic.
+///
+/// ```
+/// # use juice_sdk_rs::{client::Client, transports::MockTransport};
+/// # use serde_json::json;
+/// let mock = MockTransport::new();
+/// mock.expect("juice_blockNumber")
+///     .with_params(vec![json!("sys")])
+///     .times(1)
+///     .returns(json!("0x10"));
+///
+/// let client = Client::new(mock.clone(), true);
+/// let number = futures::executor::block_on(client.block_number("sys".into())).unwrap();
+///
+/// assert_eq!(number.as_u64(), 0x10);
+/// mock.verify();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct MockTransport {
+    state: Arc<Mutex<State>>,
+}
+
+impl MockTransport {
+    /// Create a new transport without any expectation.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start programming an expectation for the given method.
+    ///
+    /// The expectation is registered once its response is set.
+    pub fn expect(&self, method: &str) -> ExpectationBuilder<'_> {
+        ExpectationBuilder {
+            mock: self,
+            method: method.into(),
+            params: None,
+            times: None,
+        }
+    }
+
+    /// Returns every request received, in order.
+    pub fn calls(&self) -> Vec<(String, Vec<Value>)> {
+        self.state().calls.clone()
+    }
+
+    /// Returns the number of requests received for the given method.
+    pub fn call_count(&self, method: &str) -> usize {
+        self.state()
+            .calls
+            .iter()
+            .filter(|(m, _)| m == method)
+            .count()
+    }
+
+    /// Returns the number of requests received for the given method and params.
+    pub fn call_count_with(&self, method: &str, params: &[Value]) -> usize {
+        self.state()
+            .calls
+            .iter()
+            .filter(|(m, p)| m == method && p.as_slice() == params)
+            .count()
+    }
+
+    /// Panics unless the given method was requested exactly `times` times.
+    pub fn assert_called(&self, method: &str, times: usize) {
+        let count = self.call_count(method);
+        assert_eq!(
+            count, times,
+            "Expected {} to be called {} times, got {}",
+            method, times, count
+        );
+    }
+
+    /// Panics if a request matched no expectation, or if an expectation
+    /// with an expected number of calls was not called that many times.
+    pub fn verify(&self) {
+        let state = self.state();
+        assert!(
+            state.unexpected.is_empty(),
+            "Unexpected requests: {:?}",
+            state.unexpected
+        );
+        for expectation in &state.expectations {
+            if let Some(times) = expectation.times {
+                assert_eq!(
+                    expectation.calls, times,
+                    "Expected {:?} to be called {} times, got {}",
+                    expectation, times, expectation.calls
+                );
+            }
+        }
+    }
+
+    fn state(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().expect("mock lock poisoned")
+    }
+}
+
+impl Transport for MockTransport {
+    type Out = future::Ready<Result<Value>>;
+
+    fn prepare(&self, method: &str, params: Vec<Value>) -> (RequestId, Call) {
+        let mut state = self.state();
+        state.id += 1;
+        (state.id, helpers::build_request(state.id, method, params))
+    }
+
+    fn send(&self, id: RequestId, request: Call) -> Self::Out {
+        let method = helpers::call_method(&request).to_owned();
+        let params = match request {
+            Call::MethodCall(call) => match call.params {
+                Params::Array(params) => params,
+                Params::Map(map) => vec![Value::Object(map)],
+                Params::None => vec![],
+            },
+            _ => vec![],
+        };
+
+        let responder = {
+            let mut state = self.state();
+            state.calls.push((method.clone(), params.clone()));
+            let expectation = state
+                .expectations
+                .iter_mut()
+                .find(|expectation| expectation.matches(&method, &params));
+            match expectation {
+                Some(expectation) => {
+                    expectation.calls += 1;
+                    Some(expectation.responder.clone())
+                }
+                None => {
+                    log::debug!("[id:{}] unexpected request {} {:?}", id, method, params);
+                    state.unexpected.push((method, params.clone()));
+                    None
+                }
+            }
+        };
+
+        future::ready(match responder {
+            Some(responder) => responder(&params),
+            None => Err(Error::Unreachable),
+        })
+    }
+}
+
+/// Expectation being programmed on a `MockTransport`.
+#[derive(Debug)]
+#[must_use = "the expectation is registered once its response is set"]
+pub struct ExpectationBuilder<'a> {
+    mock: &'a MockTransport,
+    method: String,
+    params: Option<Vec<Value>>,
+    times: Option<usize>,
+}
+
+impl ExpectationBuilder<'_> {
+    /// Only match requests with the given params.
+    pub fn with_params(mut self, params: Vec<Value>) -> Self {
+        self.params = Some(params);
+        self
+    }
+
+    /// Expect exactly `times` matching requests. Further requests are left
+    /// to the next matching expectation.
+    pub fn times(mut self, times: usize) -> Self {
+        self.times = Some(times);
+        self
+    }
+
+    /// Answer matching requests with the given value.
+    pub fn returns(self, value: Value) {
+        self.responds_with(move |_| Ok(value.clone()))
+    }
+
+    /// Answer matching requests with the given node error, recognized as
+    /// a `NodeError` like the errors of a real node.
+    pub fn returns_error(self, error: jsonrpc_core::Error) {
+        self.responds_with(move |_| Err(error.clone().into()))
+    }
+
+    /// Answer matching requests with the result of the given function,
+    /// called with the params of the request.
+    pub fn responds_with<F>(self, responder: F)
+    where
+        F: Fn(&[Value]) -> Result<Value> + Send + Sync + 'static,
+    {
+        self.mock.state().expectations.push(Expectation {
+            method: self.method,
+            params: self.params,
+            times: self.times,
+            calls: 0,
+            responder: Arc::new(responder),
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn should_match_expectations_by_params() {
+        // given
+        let mock = MockTransport::new();
+        mock.expect("juice_getBalance")
+            .with_params(vec![json!("a")])
+            .returns(json!("0x1"));
+        mock.expect("juice_getBalance").returns(json!("0x0"));
+
+        // when
+        let a = mock.execute("juice_getBalance", vec![json!("a")]).await;
+        let b = mock.execute("juice_getBalance", vec![json!("b")]).await;
+
+        // then
+        assert_eq!(a, Ok(json!("0x1")));
+        assert_eq!(b, Ok(json!("0x0")));
+        assert_eq!(mock.call_count_with("juice_getBalance", &[json!("a")]), 1);
+        mock.assert_called("juice_getBalance", 2);
+        mock.verify();
+    }
+
+    #[tokio::test]
+    async fn should_move_to_next_expectation_when_exhausted() {
+        let mock = MockTransport::new();
+        mock.expect("juice_sendRawTransaction")
+            .times(1)
+            .returns_error(jsonrpc_core::Error::invalid_params("nonce too low"));
+        mock.expect("juice_sendRawTransaction")
+            .responds_with(|_| Err(Error::Transport("connection reset".into())));
+
+        let first = mock.execute("juice_sendRawTransaction", vec![]).await;
+        let second = mock.execute("juice_sendRawTransaction", vec![]).await;
+
+        assert_eq!(
+            first.unwrap_err().node_error(),
+            Some(&crate::error::NodeError::NonceTooLow)
+        );
+        assert_eq!(second, Err(Error::Transport("connection reset".into())));
+        mock.verify();
+    }
+
+    #[tokio::test]
+    #[should_panic(expected = "Unexpected requests")]
+    async fn should_report_unexpected_requests() {
+        let mock = MockTransport::new();
+
+        let result = mock.execute("juice_blockNumber", vec![]).await;
+
+        assert_eq!(result, Err(Error::Unreachable));
+        mock.verify();
+    }
+
+    #[test]
+    #[should_panic(expected = "to be called 2 times, got 1")]
+    fn should_report_missing_calls() {
+        let mock = MockTransport::new();
+        mock.expect("juice_blockNumber")
+            .times(2)
+            .returns(json!("0x1"));
+
+        futures::executor::block_on(mock.execute("juice_blockNumber", vec![])).unwrap();
+
+        mock.verify();
+    }
+}
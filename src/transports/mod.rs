@@ -1,6 +1,32 @@
+pub mod cached;
+pub use cached::Cached;
+
 pub mod http;
 pub use http::Http;
 
+pub mod instrumented;
+pub use instrumented::{InMemoryMetrics, Instrumented, Metrics};
+
+pub mod load_balanced;
+pub use load_balanced::LoadBalanced;
+
+#[cfg(any(test, feature = "test-utils"))]
+pub mod mock;
+#[cfg(any(test, feature = "test-utils"))]
+pub use mock::MockTransport;
+
+pub mod quorum;
+pub use quorum::Quorum;
+
+pub mod rate_limited;
+pub use rate_limited::RateLimited;
+
+pub mod recording;
+pub use recording::{Recording, Replay};
+
+pub mod retry;
+pub use retry::Retry;
+
 impl From<url::ParseError> for crate::Error {
     fn from(err: url::ParseError) -> Self {
         crate::Error::Transport(format!("failed to parse url: {}", err))
@@ -0,0 +1,196 @@
+use crate::{
+    error::{Error, Result},
+    RequestId, Transport,
+};
+use futures::{
+    future::{BoxFuture, FutureExt},
+    stream::{FuturesUnordered, StreamExt},
+};
+use jsonrpc_core::types::{Call, Value};
+use std::sync::Arc;
+
+/// Transport cross-checking responses of several nodes.
+///
+/// Every request is sent to all the nodes; the response is returned as soon
+/// as `threshold` of them returned the same value, or the same RPC error
+/// (code, message and data). Otherwise the request fails with
+/// `Error::QuorumNotReached` carrying the response of every node.
+///
+/// Meant for audit-critical reads, state changing calls are broadcast to all
+/// the nodes as well.
+#[derive(Debug, Clone)]
+pub struct Quorum<T> {
+    transports: Arc<Vec<T>>,
+    threshold: usize,
+}
+
+impl<T: Transport> Quorum<T> {
+    /// Create a new transport requiring `threshold` out of the given nodes
+    /// to agree.
+    ///
+    /// Panics if `threshold` is zero or higher than the number of nodes.
+    pub fn new(transports: Vec<T>, threshold: usize) -> Self {
+        assert!(
+            threshold > 0 && threshold <= transports.len(),
+            "threshold must be between 1 and the number of nodes"
+        );
+        Self {
+            transports: Arc::new(transports),
+            threshold,
+        }
+    }
+
+    /// Create a new transport requiring a strict majority of the nodes.
+    pub fn majority(transports: Vec<T>) -> Self {
+        let threshold = transports.len() / 2 + 1;
+        Self::new(transports, threshold)
+    }
+
+    /// Returns the number of matching responses required.
+    pub fn threshold(&self) -> usize {
+        self.threshold
+    }
+}
+
+impl<T> Transport for Quorum<T>
+where
+    T: Transport + Send + Sync + 'static,
+    T::Out: Send,
+{
+    type Out = BoxFuture<'static, Result<Value>>;
+
+    fn prepare(&self, method: &str, params: Vec<Value>) -> (RequestId, Call) {
+        self.transports[0].prepare(method, params)
+    }
+
+    fn send(&self, id: RequestId, request: Call) -> Self::Out {
+        let this = self.clone();
+        Box::pin(async move {
+            let mut pending = this
+                .transports
+                .iter()
+                .enumerate()
+                .map(|(idx, transport)| transport.send(id, request.clone()).map(move |r| (idx, r)))
+                .collect::<FuturesUnordered<_>>();
+
+            let mut responses: Vec<(usize, Result<Value>)> =
+                Vec::with_capacity(this.transports.len());
+            while let Some((idx, response)) = pending.next().await {
+                let matching = responses
+                    .iter()
+                    .filter(|(_, other)| agree(&response, other))
+                    .count();
+                if matching + 1 >= this.threshold && is_comparable(&response) {
+                    return response;
+                }
+                responses.push((idx, response));
+            }
+
+            log::debug!("[id:{}] quorum not reached: {:?}", id, responses);
+            Err(Error::QuorumNotReached {
+                required: this.threshold,
+                responses,
+            })
+        })
+    }
+}
+
+/// Returns true for the responses a node may agree on: values and errors
+/// returned by the node itself.
+fn is_comparable(response: &Result<Value>) -> bool {
+    match response {
+        Ok(_) => true,
+        Err(err) => rpc_error(err).is_some(),
+    }
+}
+
+/// Returns true if both nodes returned the same value or the same RPC error.
+fn agree(a: &Result<Value>, b: &Result<Value>) -> bool {
+    match (a, b) {
+        (Ok(a), Ok(b)) => a == b,
+        (Err(a), Err(b)) => match (rpc_error(a), rpc_error(b)) {
+            (Some(a), Some(b)) => a == b,
+            _ => false,
+        },
+        _ => false,
+    }
+}
+
+fn rpc_error(err: &Error) -> Option<&jsonrpc_core::Error> {
+    match err {
+        Error::Rpc(err) | Error::Node { error: err, .. } => Some(err),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::tests::FlakyTransport as Flaky;
+
+    fn ok(value: &str) -> Result<Value> {
+        Ok(Value::String(value.into()))
+    }
+
+    #[tokio::test]
+    async fn should_return_agreed_response() {
+        let transport = Quorum::majority(vec![
+            Flaky::new(vec![ok("0x1")]),
+            Flaky::new(vec![ok("0x2")]),
+            Flaky::new(vec![ok("0x1")]),
+        ]);
+
+        let result = transport.execute("juice_getBalance", vec![]).await;
+
+        assert_eq!(result, ok("0x1"));
+    }
+
+    #[tokio::test]
+    async fn should_fail_without_quorum() {
+        // given
+        let transport = Quorum::new(
+            vec![
+                Flaky::new(vec![ok("0x1")]),
+                Flaky::new(vec![ok("0x2")]),
+                Flaky::new(vec![]),
+            ],
+            2,
+        );
+
+        // when
+        let result = transport.execute("juice_getBalance", vec![]).await;
+
+        // then
+        assert_eq!(
+            result,
+            Err(Error::QuorumNotReached {
+                required: 2,
+                responses: vec![(0, ok("0x1")), (1, ok("0x2")), (2, Err(Error::Unreachable))],
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn should_return_agreed_error() {
+        // given
+        let reverted = || {
+            let mut err = jsonrpc_core::Error::new(jsonrpc_core::ErrorCode::ServerError(3));
+            err.message = "execution reverted".into();
+            err.data = Some(Value::String("0x08c379a0".into()));
+            err
+        };
+        let mut other = reverted();
+        other.data = Some(Value::String("0x4e487b71".into()));
+        let transport = Quorum::majority(vec![
+            Flaky::new(vec![Err(Error::Rpc(reverted()))]),
+            Flaky::new(vec![Err(Error::Rpc(other))]),
+            Flaky::new(vec![Err(Error::Rpc(reverted()))]),
+        ]);
+
+        // when
+        let result = transport.execute("juice_call", vec![]).await;
+
+        // then
+        assert_eq!(result, Err(Error::Rpc(reverted())));
+    }
+}
@@ -0,0 +1,340 @@
+use crate::{
+    error::{Error, Result},
+    helpers, RequestId, Transport,
+};
+use futures::future::BoxFuture;
+use jsonrpc_core::types::{Call, Value};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+
+/// Default number of times a request rejected with `429 Too Many Requests`
+/// is sent again.
+pub const DEFAULT_MAX_THROTTLED_RETRIES: usize = 3;
+/// Delay used when a `429 Too Many Requests` comes without `Retry-After`.
+pub const DEFAULT_THROTTLE_DELAY: Duration = Duration::from_secs(1);
+
+/// Limits applied to requests.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Limits {
+    /// Maximum number of requests per second
+    pub qps: Option<f64>,
+    /// Number of requests which may be sent at once after an idle period
+    pub burst: u32,
+    /// Maximum number of requests waiting for a response
+    pub max_in_flight: Option<usize>,
+}
+
+impl Limits {
+    /// No limits at all.
+    pub fn none() -> Self {
+        Self::default()
+    }
+
+    /// Limit the number of requests per second.
+    pub fn qps(mut self, qps: f64) -> Self {
+        self.qps = Some(qps);
+        self
+    }
+
+    /// Set the number of requests which may be sent at once after an idle
+    /// period. Defaults to one.
+    pub fn burst(mut self, burst: u32) -> Self {
+        self.burst = burst;
+        self
+    }
+
+    /// Limit the number of requests waiting for a response.
+    pub fn max_in_flight(mut self, max_in_flight: usize) -> Self {
+        self.max_in_flight = Some(max_in_flight);
+        self
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    rate: f64,
+    capacity: f64,
+    tokens: f64,
+    updated: Instant,
+}
+
+impl Bucket {
+    /// Takes a token, or returns how long to wait for the next one.
+    fn take(&mut self) -> Option<Duration> {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.updated).as_secs_f64();
+        self.tokens = (self.tokens + elapsed * self.rate).min(self.capacity);
+        self.updated = now;
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            None
+        } else {
+            Some(Duration::from_secs_f64((1.0 - self.tokens) / self.rate))
+        }
+    }
+}
+
+#[derive(Debug)]
+struct Limiter {
+    bucket: Option<Mutex<Bucket>>,
+    in_flight: Option<Arc<Semaphore>>,
+}
+
+impl Limiter {
+    fn new(limits: Limits) -> Self {
+        let bucket = limits.qps.filter(|qps| *qps > 0.0).map(|rate| {
+            let capacity = f64::from(limits.burst.max(1));
+            Mutex::new(Bucket {
+                rate,
+                capacity,
+                tokens: capacity,
+                updated: Instant::now(),
+            })
+        });
+        let in_flight = limits
+            .max_in_flight
+            .map(|max| Arc::new(Semaphore::new(max.max(1))));
+        Self { bucket, in_flight }
+    }
+
+    /// Waits until the request may be sent, returns the in-flight permit
+    /// to hold until the response is received.
+    async fn acquire(&self) -> Option<OwnedSemaphorePermit> {
+        if let Some(ref bucket) = self.bucket {
+            loop {
+                let wait = bucket.lock().expect("bucket lock poisoned").take();
+                match wait {
+                    Some(wait) => tokio::time::sleep(wait).await,
+                    None => break,
+                }
+            }
+        }
+        match self.in_flight {
+            Some(ref semaphore) => semaphore.clone().acquire_owned().await.ok(),
+            None => None,
+        }
+    }
+}
+
+/// Transport wrapper limiting the rate and the concurrency of requests.
+///
+/// Global limits apply to all requests, limits of a method apply to its
+/// requests on top of the global ones. Requests rejected by the server with
+/// `429 Too Many Requests` pause all requests for the `Retry-After` delay,
+/// then are sent again.
+#[derive(Debug, Clone)]
+pub struct RateLimited<T> {
+    inner: T,
+    global: Arc<Limiter>,
+    methods: Arc<HashMap<String, Arc<Limiter>>>,
+    paused_until: Arc<Mutex<Option<Instant>>>,
+    max_throttled_retries: usize,
+}
+
+impl<T: Transport> RateLimited<T> {
+    /// Create a new transport applying the given limits to all requests.
+    pub fn new(inner: T, limits: Limits) -> Self {
+        Self {
+            inner,
+            global: Arc::new(Limiter::new(limits)),
+            methods: Default::default(),
+            paused_until: Default::default(),
+            max_throttled_retries: DEFAULT_MAX_THROTTLED_RETRIES,
+        }
+    }
+
+    /// Apply additional limits to requests of the given method.
+    pub fn method(mut self, method: &str, limits: Limits) -> Self {
+        Arc::make_mut(&mut self.methods).insert(method.into(), Arc::new(Limiter::new(limits)));
+        self
+    }
+
+    /// Set how many times a request rejected with `429 Too Many Requests`
+    /// is sent again.
+    pub fn max_throttled_retries(mut self, retries: usize) -> Self {
+        self.max_throttled_retries = retries;
+        self
+    }
+
+    /// Returns the wrapped transport.
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Waits until the pause requested by the server is over.
+    async fn wait_for_pause(&self) {
+        loop {
+            let paused_until = *self.paused_until.lock().expect("pause lock poisoned");
+            match paused_until {
+                Some(until) if until > Instant::now() => {
+                    tokio::time::sleep_until(until.into()).await
+                }
+                _ => break,
+            }
+        }
+    }
+
+    fn pause(&self, delay: Duration) {
+        let until = Instant::now() + delay;
+        let mut paused_until = self.paused_until.lock().expect("pause lock poisoned");
+        if !matches!(*paused_until, Some(current) if current >= until) {
+            *paused_until = Some(until);
+        }
+    }
+}
+
+impl<T> Transport for RateLimited<T>
+where
+    T: Transport + Send + Sync + 'static,
+    T::Out: Send,
+{
+    type Out = BoxFuture<'static, Result<Value>>;
+
+    fn prepare(&self, method: &str, params: Vec<Value>) -> (RequestId, Call) {
+        self.inner.prepare(method, params)
+    }
+
+    fn send(&self, id: RequestId, request: Call) -> Self::Out {
+        let this = self.clone();
+        let limiter = self.methods.get(helpers::call_method(&request)).cloned();
+        Box::pin(async move {
+            let mut throttled = 0;
+            loop {
+                this.wait_for_pause().await;
+                // A request held back by its method must not hold a global
+                // slot, or it would block the other methods.
+                let _method = match limiter {
+                    Some(ref limiter) => limiter.acquire().await,
+                    None => None,
+                };
+                let _global = this.global.acquire().await;
+                let result = this.inner.send(id, request.clone()).await;
+                match result {
+                    Err(Error::HttpStatus {
+                        status: 429,
+                        retry_after,
+                    }) if throttled < this.max_throttled_retries => {
+                        let delay = retry_after.unwrap_or(DEFAULT_THROTTLE_DELAY);
+                        log::debug!("[id:{}] throttled, pausing for {:?}", id, delay);
+                        this.pause(delay);
+                        throttled += 1;
+                    }
+                    result => return result,
+                }
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::helpers::tests::FlakyTransport as Flaky;
+
+    fn ok() -> Result<Value> {
+        Ok(Value::String("0x1".into()))
+    }
+
+    #[tokio::test]
+    async fn should_limit_requests_per_second() {
+        // given
+        let inner = Flaky::new(vec![ok(), ok(), ok()]);
+        let transport = RateLimited::new(inner, Limits::none().qps(20.0));
+
+        // when
+        let started = Instant::now();
+        for _ in 0..3 {
+            transport.execute("juice_getBalance", vec![]).await.unwrap();
+        }
+
+        // then
+        assert!(started.elapsed() >= Duration::from_millis(90));
+    }
+
+    #[tokio::test]
+    async fn should_apply_method_limits() {
+        // given
+        let inner = Flaky::new(vec![ok(), ok(), ok(), ok(), ok()]);
+        let transport =
+            RateLimited::new(inner, Limits::none()).method("juice_call", Limits::none().qps(10.0));
+
+        // when
+        let started = Instant::now();
+        for _ in 0..3 {
+            transport.execute("juice_getBalance", vec![]).await.unwrap();
+        }
+        let unlimited = started.elapsed();
+        for _ in 0..2 {
+            transport.execute("juice_call", vec![]).await.unwrap();
+        }
+
+        // then
+        assert!(unlimited < Duration::from_millis(50));
+        assert!(started.elapsed() >= Duration::from_millis(90));
+    }
+
+    #[tokio::test]
+    async fn should_not_block_other_methods_while_throttled() {
+        // given
+        let inner = Flaky::new(vec![ok(), ok(), ok()]);
+        let transport = RateLimited::new(inner, Limits::none().max_in_flight(1))
+            .method("juice_call", Limits::none().qps(5.0));
+        transport.execute("juice_call", vec![]).await.unwrap();
+
+        // when
+        let throttled = transport.execute("juice_call", vec![]);
+        let other = async {
+            let started = Instant::now();
+            transport.execute("juice_getBalance", vec![]).await.unwrap();
+            started.elapsed()
+        };
+        let (throttled, other) = tokio::join!(throttled, other);
+
+        // then
+        assert_eq!(throttled, ok());
+        assert!(other < Duration::from_millis(100));
+    }
+
+    #[tokio::test]
+    async fn should_respect_retry_after() {
+        // given
+        let inner = Flaky::new(vec![
+            Err(Error::HttpStatus {
+                status: 429,
+                retry_after: Some(Duration::from_millis(50)),
+            }),
+            ok(),
+        ]);
+        let transport = RateLimited::new(inner.clone(), Limits::none());
+
+        // when
+        let started = Instant::now();
+        let result = transport.execute("juice_sendTransaction", vec![]).await;
+
+        // then
+        assert_eq!(result, ok());
+        assert_eq!(inner.calls(), 2);
+        assert!(started.elapsed() >= Duration::from_millis(50));
+    }
+
+    #[tokio::test]
+    async fn should_give_up_when_throttled_too_often() {
+        let throttled = || {
+            Err(Error::HttpStatus {
+                status: 429,
+                retry_after: Some(Duration::from_millis(1)),
+            })
+        };
+        let inner = Flaky::new(vec![throttled(), throttled()]);
+        let transport = RateLimited::new(inner.clone(), Limits::none()).max_throttled_retries(1);
+
+        let result = transport.execute("juice_getBalance", vec![]).await;
+
+        assert_eq!(result, throttled());
+        assert_eq!(inner.calls(), 2);
+    }
+}
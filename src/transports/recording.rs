@@ -0,0 +1,250 @@
+use crate::{
+    error::{Error, Result},
+    helpers, RequestId, Transport,
+};
+use futures::future::{self, BoxFuture};
+use jsonrpc_core::types::{Call, Params, Value};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, VecDeque},
+    fs,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+/// A recorded request and its response.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Exchange {
+    /// Method of the request
+    pub method: String,
+    /// Parameters of the request
+    pub params: Vec<Value>,
+    /// Result returned by the node
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    /// Error returned by the node
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<jsonrpc_core::Error>,
+}
+
+impl Exchange {
+    fn response(&self) -> Result<Value> {
+        match self.error {
+            Some(ref err) => Err(err.clone().into()),
+            None => Ok(self.result.clone().unwrap_or(Value::Null)),
+        }
+    }
+}
+
+fn method_and_params(call: &Call) -> (String, Vec<Value>) {
+    let params = match call {
+        Call::MethodCall(call) => match call.params {
+            Params::Array(ref params) => params.clone(),
+            Params::Map(ref map) => vec![Value::Object(map.clone())],
+            Params::None => vec![],
+        },
+        _ => vec![],
+    };
+    (helpers::call_method(call).to_owned(), params)
+}
+
+/// Transport recording requests and responses of the wrapped transport.
+///
+/// Responses returned by the node, including RPC errors, are recorded and
+/// may be written to a JSON fixture served by `Replay`. Transport failures
+/// are not recorded.
+#[derive(Debug, Clone)]
+pub struct Recording<T> {
+    inner: T,
+    path: Arc<PathBuf>,
+    exchanges: Arc<Mutex<Vec<Exchange>>>,
+}
+
+impl<T: Transport> Recording<T> {
+    /// Create a new transport recording to the given fixture file.
+    pub fn new(inner: T, path: impl AsRef<Path>) -> Self {
+        Self {
+            inner,
+            path: Arc::new(path.as_ref().to_owned()),
+            exchanges: Default::default(),
+        }
+    }
+
+    /// Returns the wrapped transport.
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Returns the exchanges recorded so far.
+    pub fn exchanges(&self) -> Vec<Exchange> {
+        self.exchanges.lock().expect("lock poisoned").clone()
+    }
+
+    /// Writes the recorded exchanges to the fixture file.
+    pub fn save(&self) -> Result<()> {
+        let data = serde_json::to_vec_pretty(&self.exchanges())?;
+        fs::write(self.path.as_ref(), data)?;
+        Ok(())
+    }
+}
+
+impl<T> Transport for Recording<T>
+where
+    T: Transport + Send + Sync + 'static,
+    T::Out: Send,
+{
+    type Out = BoxFuture<'static, Result<Value>>;
+
+    fn prepare(&self, method: &str, params: Vec<Value>) -> (RequestId, Call) {
+        self.inner.prepare(method, params)
+    }
+
+    fn send(&self, id: RequestId, request: Call) -> Self::Out {
+        let (method, params) = method_and_params(&request);
+        let response = self.inner.send(id, request);
+        let exchanges = self.exchanges.clone();
+        Box::pin(async move {
+            let response = response.await;
+            let (result, error) = match response {
+                Ok(ref value) => (Some(value.clone()), None),
+                Err(ref err) => match err.rpc_error() {
+                    Some(err) => (None, Some(err.clone())),
+                    None => return response,
+                },
+            };
+            exchanges.lock().expect("lock poisoned").push(Exchange {
+                method,
+                params,
+                result,
+                error,
+            });
+            response
+        })
+    }
+}
+
+/// Transport serving responses recorded by `Recording`.
+///
+/// Requests are matched by method and parameters. When the same request was
+/// recorded several times, the responses are served in the recorded order,
+/// the last one being repeated. Unknown requests fail with
+/// `Error::Unreachable`.
+#[derive(Debug, Clone)]
+pub struct Replay {
+    responses: Arc<Mutex<HashMap<String, VecDeque<Exchange>>>>,
+    id: Arc<Mutex<RequestId>>,
+}
+
+impl Replay {
+    /// Create a new transport serving the given exchanges.
+    pub fn new(exchanges: Vec<Exchange>) -> Self {
+        let mut responses: HashMap<String, VecDeque<Exchange>> = HashMap::new();
+        for exchange in exchanges {
+            let key = helpers::to_string(&(&exchange.method, &exchange.params));
+            responses.entry(key).or_default().push_back(exchange);
+        }
+        Self {
+            responses: Arc::new(Mutex::new(responses)),
+            id: Default::default(),
+        }
+    }
+
+    /// Create a new transport serving the exchanges of the fixture file.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        let exchanges = serde_json::from_slice(&fs::read(path)?)?;
+        Ok(Self::new(exchanges))
+    }
+
+    fn response(&self, method: &str, params: &[Value]) -> Option<Result<Value>> {
+        let key = helpers::to_string(&(method, params));
+        let mut responses = self.responses.lock().expect("lock poisoned");
+        let recorded = responses.get_mut(&key)?;
+        let exchange = if recorded.len() > 1 {
+            recorded.pop_front()?
+        } else {
+            recorded.front()?.clone()
+        };
+        Some(exchange.response())
+    }
+}
+
+impl Transport for Replay {
+    type Out = future::Ready<Result<Value>>;
+
+    fn prepare(&self, method: &str, params: Vec<Value>) -> (RequestId, Call) {
+        let mut id = self.id.lock().expect("lock poisoned");
+        *id += 1;
+        (*id, helpers::build_request(*id, method, params))
+    }
+
+    fn send(&self, id: RequestId, request: Call) -> Self::Out {
+        let (method, params) = method_and_params(&request);
+        future::ready(self.response(&method, &params).unwrap_or_else(|| {
+            log::debug!(
+                "[id:{}] no recorded response for {} {:?}",
+                id,
+                method,
+                params
+            );
+            Err(Error::Unreachable)
+        }))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{client::Client, helpers::tests::FlakyTransport as Flaky, types::U64};
+    use serde_json::json;
+
+    #[tokio::test]
+    async fn should_record_and_replay() {
+        // given
+        let path = std::env::temp_dir().join(format!("juice-fixture-{}.json", std::process::id()));
+        let inner = Flaky::new(vec![
+            Ok(json!("0x10")),
+            Err(jsonrpc_core::Error::invalid_params("unknown ledger").into()),
+        ]);
+        let recording = Recording::new(inner, &path);
+        let client = Client::new(recording.clone(), true);
+        let number = client.block_number("sys".into()).await;
+        let missing = client.block_number("nope".into()).await;
+        recording.save().unwrap();
+
+        // when
+        let replay = Replay::from_file(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let client = Client::new(replay, true);
+
+        // then
+        assert_eq!(number, Ok(U64::from(0x10)));
+        assert_eq!(client.block_number("sys".into()).await, number);
+        assert_eq!(client.block_number("nope".into()).await, missing);
+        assert_eq!(
+            client.block_number("test".into()).await,
+            Err(Error::Unreachable)
+        );
+    }
+
+    #[test]
+    fn should_replay_repeated_requests_in_order() {
+        let exchange = |result: &str| Exchange {
+            method: "juice_blockNumber".into(),
+            params: vec![json!("sys")],
+            result: Some(json!(result)),
+            error: None,
+        };
+        let replay = Replay::new(vec![exchange("0x1"), exchange("0x2")]);
+
+        let numbers = (0..3)
+            .map(|_| {
+                futures::executor::block_on(replay.execute("juice_blockNumber", vec![json!("sys")]))
+            })
+            .collect::<Vec<_>>();
+
+        assert_eq!(
+            numbers,
+            vec![Ok(json!("0x1")), Ok(json!("0x2")), Ok(json!("0x2"))]
+        );
+    }
+}
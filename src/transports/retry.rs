@@ -0,0 +1,267 @@
+use crate::{
+    error::{NodeError, Result},
+    helpers, RequestId, Transport,
+};
+use futures::future::BoxFuture;
+use jsonrpc_core::types::{Call, Params, Value};
+use std::{
+    collections::hash_map::RandomState,
+    hash::{BuildHasher, Hasher},
+    time::{Duration, SystemTime},
+};
+
+/// Default maximum number of attempts, including the first one.
+pub const DEFAULT_MAX_ATTEMPTS: usize = 3;
+/// Default delay before the first retry.
+pub const DEFAULT_BASE_DELAY: Duration = Duration::from_millis(200);
+/// Default upper bound of the delay between two attempts.
+pub const DEFAULT_MAX_DELAY: Duration = Duration::from_secs(5);
+
+/// Read-only methods which don't follow the `juice_get*` naming.
+const READ_ONLY_METHODS: &[&str] = &[
+    "juice_blockNumber",
+    "juice_call",
+    "juice_estimateGas",
+    "juice_gasPrice",
+    "juice_syncing",
+];
+
+const SEND_RAW_TRANSACTION: &str = "juice_sendRawTransaction";
+
+/// Transport wrapper retrying failed requests with exponential backoff.
+///
+/// Only requests which are safe to repeat are retried:
+/// - read-only calls (`juice_get*` and friends),
+/// - `juice_sendRawTransaction`, since the same signed bytes are sent again.
+///   If the node answers that the transaction is already known, the
+///   transaction hash is returned as success.
+///
+/// Other methods are passed through untouched. Only transport failures are
+/// retried, errors returned by the node are not. A `Retry-After` delay sent
+/// by the server is respected.
+#[derive(Debug, Clone)]
+pub struct Retry<T> {
+    inner: T,
+    max_attempts: usize,
+    base_delay: Duration,
+    max_delay: Duration,
+}
+
+impl<T: Transport> Retry<T> {
+    /// Create a new retrying transport with default settings.
+    pub fn new(inner: T) -> Self {
+        Self {
+            inner,
+            max_attempts: DEFAULT_MAX_ATTEMPTS,
+            base_delay: DEFAULT_BASE_DELAY,
+            max_delay: DEFAULT_MAX_DELAY,
+        }
+    }
+
+    /// Set the maximum number of attempts, including the first one.
+    pub fn max_attempts(mut self, max_attempts: usize) -> Self {
+        self.max_attempts = max_attempts.max(1);
+        self
+    }
+
+    /// Set the delay before the first retry, doubled on every attempt.
+    pub fn base_delay(mut self, base_delay: Duration) -> Self {
+        self.base_delay = base_delay;
+        self
+    }
+
+    /// Set the upper bound of the delay between two attempts.
+    pub fn max_delay(mut self, max_delay: Duration) -> Self {
+        self.max_delay = max_delay;
+        self
+    }
+
+    /// Returns the wrapped transport.
+    pub fn inner(&self) -> &T {
+        &self.inner
+    }
+
+    /// Returns the delay before the given retry, with full jitter.
+    fn delay(&self, retry: usize) -> Duration {
+        let exp = self
+            .base_delay
+            .checked_mul(1 << retry.min(16) as u32)
+            .unwrap_or(self.max_delay)
+            .min(self.max_delay);
+        let millis = exp.as_millis() as u64;
+        if millis == 0 {
+            return exp;
+        }
+        Duration::from_millis(jitter() % (millis + 1))
+    }
+}
+
+/// Returns true if the method doesn't change any state.
+pub fn is_read_only(method: &str) -> bool {
+    method.starts_with("juice_get") || READ_ONLY_METHODS.contains(&method)
+}
+
+/// Returns the hash of the raw transaction sent by the call, if any.
+fn raw_transaction_hash(call: &Call) -> Option<Value> {
+    let params = match call {
+        Call::MethodCall(call) if call.method == SEND_RAW_TRANSACTION => &call.params,
+        _ => return None,
+    };
+    let rlp = match params {
+        Params::Array(params) => params.last()?,
+        _ => return None,
+    };
+    let rlp: crate::types::Bytes = serde_json::from_value(rlp.clone()).ok()?;
+    let hash = crate::types::H256::from(helpers::keccak256(&rlp.0));
+    Some(helpers::serialize(&hash))
+}
+
+fn jitter() -> u64 {
+    let mut hasher = RandomState::new().build_hasher();
+    if let Ok(now) = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH) {
+        hasher.write_u128(now.as_nanos());
+    }
+    hasher.finish()
+}
+
+impl<T> Transport for Retry<T>
+where
+    T: Transport + Send + Sync + 'static,
+    T::Out: Send,
+{
+    type Out = BoxFuture<'static, Result<Value>>;
+
+    fn prepare(&self, method: &str, params: Vec<Value>) -> (RequestId, Call) {
+        self.inner.prepare(method, params)
+    }
+
+    fn send(&self, id: RequestId, request: Call) -> Self::Out {
+        let this = self.clone();
+        Box::pin(async move {
+            let tx_hash = raw_transaction_hash(&request);
+            let retryable = tx_hash.is_some() || is_read_only(helpers::call_method(&request));
+            let mut attempt = 1;
+            loop {
+                let err = match this.inner.send(id, request.clone()).await {
+                    Ok(value) => return Ok(value),
+                    Err(err) => err,
+                };
+                if let Some(ref hash) = tx_hash {
+                    if err.node_error() == Some(&NodeError::AlreadyKnown) {
+                        return Ok(hash.clone());
+                    }
+                }
+                if !retryable || !err.is_retryable() || attempt >= this.max_attempts {
+                    return Err(err);
+                }
+                let delay = this
+                    .delay(attempt - 1)
+                    .max(err.retry_after().unwrap_or_default());
+                log::debug!(
+                    "[id:{}] request failed (attempt {}), retrying in {:?}: {}",
+                    id,
+                    attempt,
+                    delay,
+                    err
+                );
+                tokio::time::sleep(delay).await;
+                attempt += 1;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{error::Error, helpers::tests::FlakyTransport as Flaky};
+
+    fn transport_error() -> Error {
+        Error::Transport("connection reset".into())
+    }
+
+    fn retry(inner: Flaky) -> Retry<Flaky> {
+        Retry::new(inner)
+            .max_attempts(3)
+            .base_delay(Duration::from_millis(1))
+    }
+
+    #[tokio::test]
+    async fn should_retry_read_only_calls() {
+        // given
+        let inner = Flaky::new(vec![
+            Err(transport_error()),
+            Err(transport_error()),
+            Ok(Value::String("0x1".into())),
+        ]);
+        let transport = retry(inner.clone());
+
+        // when
+        let result = transport.execute("juice_getBalance", vec![]).await;
+
+        // then
+        assert_eq!(result, Ok(Value::String("0x1".into())));
+        assert_eq!(inner.calls(), 3);
+    }
+
+    #[tokio::test]
+    async fn should_give_up_after_max_attempts() {
+        let inner = Flaky::new(vec![]);
+        let transport = retry(inner.clone());
+
+        let result = transport.execute("juice_blockNumber", vec![]).await;
+
+        assert_eq!(result, Err(Error::Unreachable));
+        assert_eq!(inner.calls(), 3);
+    }
+
+    #[tokio::test]
+    async fn should_not_retry_state_changing_calls() {
+        let inner = Flaky::new(vec![Err(transport_error())]);
+        let transport = retry(inner.clone());
+
+        let result = transport.execute("juice_sendTransaction", vec![]).await;
+
+        assert_eq!(result, Err(transport_error()));
+        assert_eq!(inner.calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn should_not_retry_rpc_errors() {
+        let inner = Flaky::new(vec![Err(Error::Rpc(jsonrpc_core::Error::invalid_params(
+            "bad",
+        )))]);
+        let transport = retry(inner.clone());
+
+        let result = transport.execute("juice_getBalance", vec![]).await;
+
+        assert!(result.is_err());
+        assert_eq!(inner.calls(), 1);
+    }
+
+    #[tokio::test]
+    async fn should_treat_already_known_raw_transaction_as_success() {
+        // given
+        let mut known = jsonrpc_core::Error::internal_error();
+        known.message = "already known".into();
+        let inner = Flaky::new(vec![Err(transport_error()), Err(known.into())]);
+        let transport = retry(inner.clone());
+
+        // when
+        let result = transport
+            .execute(
+                "juice_sendRawTransaction",
+                vec![Value::String("sys".into()), Value::String("0x".into())],
+            )
+            .await;
+
+        // then
+        assert_eq!(
+            result,
+            Ok(Value::String(
+                "0xc5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470".into()
+            ))
+        );
+        assert_eq!(inner.calls(), 2);
+    }
+}
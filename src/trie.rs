@@ -0,0 +1,393 @@
+//! Merkle-Patricia trie roots.
+//!
+//! Recomputes the roots committed in block headers, such as
+//! `transactionsRoot` and `receiptsRoot`, from the full list of values, and
+//! verifies Merkle proofs against them.
+
+use crate::{
+    error::{Error, Result},
+    helpers,
+    types::{Bytes, H256},
+};
+use rlp::{Rlp, RlpStream};
+use std::collections::{BTreeMap, HashMap};
+
+/// Root of the empty trie, `keccak256(rlp(""))`.
+pub const EMPTY_ROOT: H256 = H256([
+    0x56, 0xe8, 0x1f, 0x17, 0x1b, 0xcc, 0x55, 0xa6, 0xff, 0x83, 0x45, 0xe6, 0x92, 0xc0, 0xf8, 0x6e,
+    0x5b, 0x48, 0xe0, 0x1b, 0x99, 0x6c, 0xad, 0xc0, 0x01, 0x62, 0x2f, 0xb5, 0xe3, 0x63, 0xb4, 0x21,
+]);
+
+/// Builds a trie in memory and computes its root.
+///
+/// ```
+/// use juice_sdk_rs::trie::TrieBuilder;
+///
+/// let root = TrieBuilder::new()
+///     .insert(b"doe", b"reindeer")
+///     .insert(b"dog", b"puppy")
+///     .root();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TrieBuilder {
+    items: BTreeMap<Vec<u8>, Vec<u8>>,
+}
+
+impl TrieBuilder {
+    /// Create an empty trie.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Insert a value, replacing the previous value of the key.
+    pub fn insert(mut self, key: impl AsRef<[u8]>, value: impl AsRef<[u8]>) -> Self {
+        self.items
+            .insert(key.as_ref().to_vec(), value.as_ref().to_vec());
+        self
+    }
+
+    /// Computes the root hash of the trie.
+    pub fn root(&self) -> H256 {
+        let items = self
+            .items
+            .iter()
+            .map(|(key, value)| (nibbles(key), value.as_slice()))
+            .collect::<Vec<_>>();
+        let mut stream = RlpStream::new();
+        encode_node(&items, &mut stream);
+        H256(helpers::keccak256(&stream.out()))
+    }
+
+    /// Returns the proof of the key: the nodes on its path, from the root.
+    ///
+    /// Proves the absence of the key if it was not inserted.
+    pub fn proof(&self, key: impl AsRef<[u8]>) -> Vec<Bytes> {
+        let items = self
+            .items
+            .iter()
+            .map(|(key, value)| (nibbles(key), value.as_slice()))
+            .collect::<Vec<_>>();
+        let mut proof = vec![];
+        collect_proof(&items, &nibbles(key.as_ref()), true, &mut proof);
+        proof
+    }
+}
+
+/// Verifies the proof of the key against the root of the trie.
+///
+/// Returns the value of the key, `None` if the proof shows that the key is
+/// absent. Fails if the proof is incomplete or does not match the root.
+pub fn verify_proof(root: H256, key: &[u8], proof: &[Bytes]) -> Result<Option<Vec<u8>>> {
+    let nodes = proof
+        .iter()
+        .map(|node| (H256(helpers::keccak256(&node.0)), node.0.as_slice()))
+        .collect::<HashMap<_, _>>();
+    let lookup = |hash: H256| {
+        nodes
+            .get(&hash)
+            .map(|node| node.to_vec())
+            .ok_or_else(|| Error::Verification(format!("proof misses trie node {:?}", hash)))
+    };
+    let invalid = |e: rlp::DecoderError| Error::Verification(format!("invalid trie node: {}", e));
+
+    if root == EMPTY_ROOT && !nodes.contains_key(&root) {
+        return Ok(None);
+    }
+    let path = nibbles(key);
+    let mut path = path.as_slice();
+    let mut node = lookup(root)?;
+    loop {
+        let rlp = Rlp::new(&node);
+        let child = match rlp.item_count().map_err(invalid)? {
+            17 => match path.split_first() {
+                None => {
+                    let value = rlp.at(16).and_then(|v| v.data()).map_err(invalid)?;
+                    return Ok(Some(value.to_vec()).filter(|value| !value.is_empty()));
+                }
+                Some((nibble, rest)) => {
+                    path = rest;
+                    rlp.at(*nibble as usize).map_err(invalid)?
+                }
+            },
+            2 => {
+                let partial = rlp.at(0).and_then(|p| p.data()).map_err(invalid)?;
+                let (partial, leaf) = decode_hex_prefix(partial)
+                    .ok_or_else(|| Error::Verification("invalid trie node path".into()))?;
+                if leaf {
+                    let value = rlp.at(1).and_then(|v| v.data()).map_err(invalid)?;
+                    return Ok(Some(value.to_vec()).filter(|_| partial == path));
+                }
+                match path.strip_prefix(partial.as_slice()) {
+                    Some(rest) => path = rest,
+                    None => return Ok(None),
+                }
+                rlp.at(1).map_err(invalid)?
+            }
+            count => {
+                return Err(Error::Verification(format!(
+                    "invalid trie node of {} items",
+                    count
+                )))
+            }
+        };
+        // Nodes shorter than a hash are inlined in their parent.
+        node = if child.is_list() {
+            child.as_raw().to_vec()
+        } else {
+            match child.data().map_err(invalid)? {
+                [] => return Ok(None),
+                hash if hash.len() == 32 => lookup(H256::from_slice(hash))?,
+                _ => return Err(Error::Verification("invalid trie node reference".into())),
+            }
+        };
+    }
+}
+
+/// Computes the root of the trie keyed by the RLP encoded index of the
+/// values, as used for the transactions and the receipts of a block.
+pub fn ordered_root<V: AsRef<[u8]>>(values: impl IntoIterator<Item = V>) -> H256 {
+    values
+        .into_iter()
+        .enumerate()
+        .fold(TrieBuilder::new(), |trie, (idx, value)| {
+            trie.insert(rlp::encode(&idx), value)
+        })
+        .root()
+}
+
+fn nibbles(key: &[u8]) -> Vec<u8> {
+    key.iter()
+        .flat_map(|byte| [byte >> 4, byte & 0x0f])
+        .collect()
+}
+
+/// Hex-prefix encoding of a path, flagging leaves.
+fn hex_prefix(nibbles: &[u8], leaf: bool) -> Vec<u8> {
+    let flag = if leaf { 0x20 } else { 0x00 };
+    let mut encoded = Vec::with_capacity(nibbles.len() / 2 + 1);
+    let rest = if nibbles.len() % 2 == 1 {
+        encoded.push(flag | 0x10 | nibbles[0]);
+        &nibbles[1..]
+    } else {
+        encoded.push(flag);
+        nibbles
+    };
+    encoded.extend(rest.chunks(2).map(|pair| (pair[0] << 4) | pair[1]));
+    encoded
+}
+
+/// Decodes a hex-prefix encoded path, returns the nibbles and the leaf flag.
+fn decode_hex_prefix(encoded: &[u8]) -> Option<(Vec<u8>, bool)> {
+    let (first, rest) = encoded.split_first()?;
+    let leaf = match first >> 4 {
+        0 | 1 => false,
+        2 | 3 => true,
+        _ => return None,
+    };
+    let mut nibbles = Vec::with_capacity(rest.len() * 2 + 1);
+    if first & 0x10 != 0 {
+        nibbles.push(first & 0x0f);
+    }
+    nibbles.extend(self::nibbles(rest));
+    Some((nibbles, leaf))
+}
+
+/// Encodes the node holding the given sorted items, with keys relative to
+/// the node.
+fn encode_node(items: &[(Vec<u8>, &[u8])], stream: &mut RlpStream) {
+    let (first, last) = match items {
+        [] => {
+            stream.append_empty_data();
+            return;
+        }
+        [(key, value)] => {
+            stream
+                .begin_list(2)
+                .append(&hex_prefix(key, true))
+                .append(value);
+            return;
+        }
+        [first, .., last] => (&first.0, &last.0),
+    };
+
+    // The items are sorted, so the first and the last keys share the
+    // longest prefix.
+    let shared = first
+        .iter()
+        .zip(last.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    if shared > 0 {
+        stream
+            .begin_list(2)
+            .append(&hex_prefix(&first[..shared], false));
+        let children = items
+            .iter()
+            .map(|(key, value)| (key[shared..].to_vec(), *value))
+            .collect::<Vec<_>>();
+        encode_child(&children, stream);
+        return;
+    }
+
+    stream.begin_list(17);
+    // Only the first key may end at this node.
+    let (value, mut rest) = match items[0].0.is_empty() {
+        true => (Some(items[0].1), &items[1..]),
+        false => (None, items),
+    };
+    for nibble in 0..16u8 {
+        let len = rest.iter().take_while(|(key, _)| key[0] == nibble).count();
+        let children = rest[..len]
+            .iter()
+            .map(|(key, value)| (key[1..].to_vec(), *value))
+            .collect::<Vec<_>>();
+        encode_child(&children, stream);
+        rest = &rest[len..];
+    }
+    match value {
+        Some(value) => stream.append(&value),
+        None => stream.append_empty_data(),
+    };
+}
+
+/// Appends a child node, inlined if its encoding is shorter than a hash.
+fn encode_child(items: &[(Vec<u8>, &[u8])], stream: &mut RlpStream) {
+    if items.is_empty() {
+        stream.append_empty_data();
+        return;
+    }
+    let mut child = RlpStream::new();
+    encode_node(items, &mut child);
+    let encoded = child.out();
+    if encoded.len() < 32 {
+        stream.append_raw(&encoded, 1);
+    } else {
+        stream.append(&H256(helpers::keccak256(&encoded)));
+    }
+}
+
+/// Collects the nodes on the path, mirroring `encode_node`. Nodes inlined
+/// in their parent are not part of the proof.
+fn collect_proof(items: &[(Vec<u8>, &[u8])], path: &[u8], root: bool, proof: &mut Vec<Bytes>) {
+    if items.is_empty() {
+        return;
+    }
+    let mut stream = RlpStream::new();
+    encode_node(items, &mut stream);
+    let encoded = stream.out();
+    if root || encoded.len() >= 32 {
+        proof.push(encoded.to_vec().into());
+    }
+    if items.len() == 1 {
+        return;
+    }
+
+    let (first, last) = (&items[0].0, &items[items.len() - 1].0);
+    let shared = first
+        .iter()
+        .zip(last.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let (prefix, rest) = match shared {
+        0 => match path.split_first() {
+            Some((nibble, rest)) => (vec![*nibble], rest),
+            None => return,
+        },
+        _ => (
+            first[..shared].to_vec(),
+            path.get(shared..).unwrap_or_default(),
+        ),
+    };
+    if !path.starts_with(&prefix) {
+        return;
+    }
+    let children = items
+        .iter()
+        .filter(|(key, _)| key.starts_with(&prefix))
+        .map(|(key, value)| (key[prefix.len()..].to_vec(), *value))
+        .collect::<Vec<_>>();
+    collect_proof(&children, rest, false, proof);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_compute_empty_root() {
+        assert_eq!(TrieBuilder::new().root(), EMPTY_ROOT);
+        assert_eq!(ordered_root(Vec::<Vec<u8>>::new()), EMPTY_ROOT);
+        assert_eq!(EMPTY_ROOT, H256(helpers::keccak256(&rlp::encode(&""))));
+    }
+
+    #[test]
+    fn should_compute_root() {
+        // given
+        let trie = TrieBuilder::new()
+            .insert(b"doe", b"reindeer")
+            .insert(b"dog", b"puppy")
+            .insert(b"dogglesworth", b"cat");
+
+        // when
+        let root = trie.root();
+
+        // then
+        assert_eq!(
+            root,
+            "0x8aad789dff2f538bca5d8ea56e8abe10f4c7ba3a5dea95fea4cd6e7c3a1168d3"
+                .parse()
+                .unwrap()
+        );
+        assert_eq!(
+            verify_proof(root, b"dog", &trie.proof(b"dog")),
+            Ok(Some(b"puppy".to_vec()))
+        );
+        assert_ne!(trie.insert(b"dog", b"kitten").root(), root);
+    }
+
+    #[test]
+    fn should_verify_proofs() {
+        // given
+        let trie = (0u64..50).fold(TrieBuilder::new(), |trie, idx| {
+            trie.insert(helpers::keccak256(&idx.to_be_bytes()), rlp::encode(&idx))
+        });
+        let root = trie.root();
+        let key = helpers::keccak256(&7u64.to_be_bytes());
+        let absent = helpers::keccak256(&70u64.to_be_bytes());
+
+        // when
+        let proof = trie.proof(key);
+
+        // then
+        assert_eq!(
+            verify_proof(root, &key, &proof),
+            Ok(Some(rlp::encode(&7u64).to_vec()))
+        );
+        assert_eq!(verify_proof(root, &absent, &trie.proof(absent)), Ok(None));
+        assert_eq!(verify_proof(EMPTY_ROOT, &key, &[]), Ok(None));
+        assert!(verify_proof(root, &key, &proof[..1]).is_err());
+        assert!(verify_proof(H256::zero(), &key, &proof).is_err());
+        let mut forged = proof.clone();
+        forged.last_mut().unwrap().0[3] ^= 1;
+        assert!(verify_proof(root, &key, &forged).is_err());
+    }
+
+    #[test]
+    fn should_encode_hex_prefix() {
+        assert_eq!(hex_prefix(&[1, 2, 3, 4, 5], false), vec![0x11, 0x23, 0x45]);
+        assert_eq!(
+            hex_prefix(&[0, 1, 2, 3, 4, 5], false),
+            vec![0x00, 0x01, 0x23, 0x45]
+        );
+        assert_eq!(
+            hex_prefix(&[0x0f, 1, 0x0c, 0x0b, 8], true),
+            vec![0x3f, 0x1c, 0xb8]
+        );
+        assert_eq!(hex_prefix(&[], true), vec![0x20]);
+        assert_eq!(
+            decode_hex_prefix(&[0x3f, 0x1c, 0xb8]),
+            Some((vec![0x0f, 1, 0x0c, 0x0b, 8], true))
+        );
+        assert_eq!(decode_hex_prefix(&[0x00, 0x01]), Some((vec![0, 1], false)));
+    }
+}
@@ -0,0 +1,74 @@
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Information about the node, returned by `admin_nodeInfo`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NodeInfo {
+    /// Node id
+    pub id: String,
+    /// Name of the node software
+    pub name: String,
+    /// Enode URL of the node
+    pub enode: String,
+    /// Ethereum node record
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enr: Option<String>,
+    /// IP address of the node
+    pub ip: String,
+    /// Listening ports
+    pub ports: Ports,
+    /// Listening address
+    #[serde(rename = "listenAddr")]
+    pub listen_addr: String,
+    /// Details of the protocols run by the node, by protocol name
+    pub protocols: BTreeMap<String, Value>,
+}
+
+/// Listening ports of a node.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Ports {
+    /// UDP discovery port
+    pub discovery: u16,
+    /// TCP listening port
+    pub listener: u16,
+}
+
+/// Information about a connected peer, returned by `admin_peers`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PeerInfo {
+    /// Node id of the peer
+    pub id: String,
+    /// Name of the peer software
+    pub name: String,
+    /// Enode URL of the peer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enode: Option<String>,
+    /// Ethereum node record of the peer
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enr: Option<String>,
+    /// Protocols advertised by the peer
+    pub caps: Vec<String>,
+    /// Connection details
+    pub network: PeerNetwork,
+    /// Details of the protocols run with the peer, by protocol name
+    pub protocols: BTreeMap<String, Value>,
+}
+
+/// Connection details of a peer.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PeerNetwork {
+    /// Local endpoint of the connection
+    #[serde(rename = "localAddress")]
+    pub local_address: String,
+    /// Remote endpoint of the connection
+    #[serde(rename = "remoteAddress")]
+    pub remote_address: String,
+    /// Is the connection initiated by the peer?
+    pub inbound: bool,
+    /// Is the peer trusted?
+    pub trusted: bool,
+    /// Is the peer a static node?
+    #[serde(rename = "static")]
+    pub is_static: bool,
+}
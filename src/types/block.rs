@@ -1,10 +1,14 @@
-use crate::types::{Address, Bytes, H256, U256, U64};
+use crate::{
+    error, helpers, trie,
+    types::{Address, Bytes, Transaction, TransactionReceipt, H2048, H256, U256, U64},
+};
 use serde::{ser::SerializeStruct, Deserialize, Serialize, Serializer};
 
 /// The block header type returned from RPC calls.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct BlockHeader {
     /// Hash of the parent
+    #[serde(rename = "parentHash")]
     pub parent_hash: H256,
     /// Miner's address
     #[serde(rename = "miner")]
@@ -31,6 +35,9 @@ pub struct BlockHeader {
     /// Extra data
     #[serde(rename = "extraData")]
     pub extra: Bytes,
+    /// Logs bloom, zero if the node does not return it
+    #[serde(rename = "logsBloom", default)]
+    pub logs_bloom: H2048,
     /// Nonce
     pub nonce: Bytes,
     /// Hash of the block
@@ -69,6 +76,9 @@ pub struct Block<TX> {
     /// Extra data
     #[serde(rename = "extraData")]
     pub extra: Bytes,
+    /// Logs bloom, zero if the node does not return it
+    #[serde(rename = "logsBloom", default)]
+    pub logs_bloom: H2048,
     /// Nonce
     pub nonce: Bytes,
     /// Hash of the block
@@ -77,6 +87,90 @@ pub struct Block<TX> {
     pub transactions: Vec<TX>,
 }
 
+impl BlockHeader {
+    /// Encodes the header with RLP, as hashed by the node.
+    ///
+    /// The fields are encoded in the order of the node's header: parent
+    /// hash, miner, state root, transactions root, receipts root, logs bloom,
+    /// number, gas limit, gas used, timestamp, extra data (including the CBFT
+    /// signature) and nonce (the VRF proof). Fails if the miner is not a
+    /// valid address.
+    pub fn rlp_bytes(&self) -> error::Result<Vec<u8>> {
+        let coinbase = helpers::address_to_bytes(&self.coinbase)?;
+        let mut stream = rlp::RlpStream::new_list(12);
+        stream
+            .append(&self.parent_hash)
+            .append(&coinbase)
+            .append(&self.root)
+            .append(&self.transaction_root)
+            .append(&self.receipts_root)
+            .append(&self.logs_bloom)
+            .append(&self.number.unwrap_or_default())
+            .append(&self.gas_limit)
+            .append(&self.gas_used)
+            .append(&self.timestamp)
+            .append(&self.extra.0)
+            .append(&self.nonce.0);
+        Ok(stream.out().to_vec())
+    }
+
+    /// Computes the hash of the header from its fields.
+    pub fn compute_hash(&self) -> error::Result<H256> {
+        Ok(H256(helpers::keccak256(&self.rlp_bytes()?)))
+    }
+
+    /// Returns true if the header hashes to its claimed `hash`.
+    pub fn verify_hash(&self) -> error::Result<bool> {
+        Ok(self.compute_hash()? == self.hash)
+    }
+}
+
+impl<TX> Block<TX> {
+    /// Returns the header of the block.
+    pub fn header(&self) -> BlockHeader {
+        BlockHeader {
+            parent_hash: self.parent_hash,
+            coinbase: self.coinbase.clone(),
+            root: self.root,
+            transaction_root: self.transaction_root,
+            receipts_root: self.receipts_root,
+            number: self.number,
+            gas_limit: self.gas_limit,
+            gas_used: self.gas_used,
+            timestamp: self.timestamp,
+            extra: self.extra.clone(),
+            logs_bloom: self.logs_bloom,
+            nonce: self.nonce.clone(),
+            hash: self.hash,
+        }
+    }
+
+    /// Returns true if the receipts hash to the block's `receiptsRoot`.
+    ///
+    /// The receipts must be given in the order of the transactions, a
+    /// missing, extra or altered receipt changes the root.
+    pub fn verify_receipts(&self, receipts: &[TransactionReceipt]) -> error::Result<bool> {
+        let receipts = receipts
+            .iter()
+            .map(TransactionReceipt::rlp_bytes)
+            .collect::<error::Result<Vec<_>>>()?;
+        Ok(trie::ordered_root(receipts) == self.receipts_root)
+    }
+}
+
+impl Block<Transaction> {
+    /// Returns true if the transactions hash to the block's
+    /// `transactionsRoot`.
+    pub fn verify_transactions(&self) -> error::Result<bool> {
+        let transactions = self
+            .transactions
+            .iter()
+            .map(Transaction::rlp_bytes)
+            .collect::<error::Result<Vec<_>>>()?;
+        Ok(trie::ordered_root(transactions) == self.transaction_root)
+    }
+}
+
 /// Block number
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum BlockNumber {
@@ -152,3 +246,122 @@ impl From<H256> for BlockId {
         BlockId::Hash(hash)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::{
+        transaction::tests::{receipt, signed_transaction},
+        ReceiptStatus, H160,
+    };
+    use serde_json::json;
+
+    #[test]
+    fn should_encode_header_in_node_order() {
+        // given
+        let mut header: BlockHeader = serde_json::from_value(json!({
+            "parentHash": H256::repeat_byte(0x11),
+            "miner": helpers::bytes_to_address("lax", &H160::repeat_byte(0x22)),
+            "stateRoot": H256::repeat_byte(0x33),
+            "transactionsRoot": trie::EMPTY_ROOT,
+            "receiptsRoot": trie::EMPTY_ROOT,
+            "logsBloom": H2048::zero(),
+            "number": "0x233",
+            "gasLimit": "0x111",
+            "gasUsed": "0x0",
+            "timestamp": "0x1111",
+            "extraData": Bytes([vec![0; 32], vec![0x44; 65]].concat()),
+            "nonce": "0x0a0b0c",
+            "hash": H256::zero(),
+        }))
+        .unwrap();
+        // RLP written out by hand: list of 525 bytes, then every field with
+        // its own prefix.
+        let empty_root = trie::EMPTY_ROOT.as_bytes().to_vec();
+        let expected = [
+            vec![0xf9, 0x02, 0x0d],
+            [vec![0xa0], vec![0x11; 32]].concat(),
+            [vec![0x94], vec![0x22; 20]].concat(),
+            [vec![0xa0], vec![0x33; 32]].concat(),
+            [vec![0xa0], empty_root.clone()].concat(),
+            [vec![0xa0], empty_root].concat(),
+            [vec![0xb9, 0x01, 0x00], vec![0; 256]].concat(),
+            vec![0x82, 0x02, 0x33],
+            vec![0x82, 0x01, 0x11],
+            vec![0x80],
+            vec![0x82, 0x11, 0x11],
+            [vec![0xb8, 0x61], vec![0; 32], vec![0x44; 65]].concat(),
+            vec![0x83, 0x0a, 0x0b, 0x0c],
+        ]
+        .concat();
+
+        // when
+        let rlp_bytes = header.rlp_bytes().unwrap();
+
+        // then
+        assert_eq!(rlp_bytes, expected);
+        let hash = H256(helpers::keccak256(&expected));
+        assert_eq!(header.compute_hash(), Ok(hash));
+        assert_eq!(header.verify_hash(), Ok(false));
+        header.hash = hash;
+        assert_eq!(header.verify_hash(), Ok(true));
+        header.extra.0[40] ^= 1;
+        assert_eq!(header.verify_hash(), Ok(false));
+    }
+
+    #[test]
+    fn should_verify_block_roots() {
+        // given
+        let tx = signed_transaction();
+        let receipt = receipt(vec![]);
+        // Roots of single item tries written out by hand: the root is the
+        // hash of the leaf `[hex_prefix(rlp(0)), item]`.
+        let tx_rlp = hex::decode("f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83").unwrap();
+        let tx_leaf = [vec![0xf8, 0x73, 0x82, 0x20, 0x80, 0xb8, 0x6e], tx_rlp].concat();
+        let receipt_rlp = [
+            vec![0xf9, 0x01, 0x08, 0x01, 0x82, 0x52, 0x08, 0xb9, 0x01, 0x00],
+            vec![0; 256],
+            vec![0xc0],
+        ]
+        .concat();
+        let receipt_leaf = [
+            vec![0xf9, 0x01, 0x11, 0x82, 0x20, 0x80, 0xb9, 0x01, 0x0b],
+            receipt_rlp,
+        ]
+        .concat();
+        let mut block: Block<Transaction> = serde_json::from_value(json!({
+            "parentHash": H256::zero(),
+            "miner": helpers::bytes_to_address("lax", &Default::default()),
+            "stateRoot": H256::zero(),
+            "transactionsRoot": H256(helpers::keccak256(&tx_leaf)),
+            "receiptsRoot": H256(helpers::keccak256(&receipt_leaf)),
+            "number": "0x1",
+            "gasLimit": "0x0",
+            "gasUsed": "0x0",
+            "timestamp": "0x0",
+            "extraData": "0x",
+            "logsBloom": H2048::zero(),
+            "nonce": "0x",
+            "hash": H256::zero(),
+            "transactions": [tx],
+        }))
+        .unwrap();
+
+        // then
+        assert_eq!(block.verify_transactions(), Ok(true));
+        assert_eq!(
+            block.verify_receipts(std::slice::from_ref(&receipt)),
+            Ok(true)
+        );
+        assert_eq!(block.verify_receipts(&[]), Ok(false));
+        let mut failed = receipt.clone();
+        failed.status = Some(ReceiptStatus::Failure);
+        assert_eq!(block.verify_receipts(&[failed]), Ok(false));
+        assert_eq!(
+            block.verify_receipts(&[receipt.clone(), receipt]),
+            Ok(false)
+        );
+        block.transactions[0].nonce = Some(10.into());
+        assert_eq!(block.verify_transactions(), Ok(false));
+    }
+}
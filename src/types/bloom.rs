@@ -0,0 +1,59 @@
+use crate::{
+    error::Result,
+    helpers,
+    types::{H2048, H256},
+};
+use ethereum_types::BloomInput;
+
+/// Membership tests of the logs bloom filter of blocks and receipts.
+///
+/// A bloom filter may give false positives but no false negatives: when it
+/// does not contain an address or a topic, no log of the block or the
+/// receipt matches it.
+pub trait BloomExt {
+    /// Returns true if logs emitted by the given address may be included.
+    ///
+    /// Fails if the address is not a valid bech32 address.
+    fn contains_address(&self, address: &str) -> Result<bool>;
+
+    /// Returns true if logs with the given topic may be included.
+    fn contains_topic(&self, topic: &H256) -> bool;
+}
+
+impl BloomExt for H2048 {
+    fn contains_address(&self, address: &str) -> Result<bool> {
+        let address = helpers::address_to_bytes(address)?;
+        Ok(self.contains_input(BloomInput::Raw(address.as_bytes())))
+    }
+
+    fn contains_topic(&self, topic: &H256) -> bool {
+        self.contains_input(BloomInput::Raw(topic.as_bytes()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{error::Error, types::H160};
+
+    #[test]
+    fn should_test_membership() {
+        // given
+        let address = H160::repeat_byte(0xb0);
+        let topic = H256::repeat_byte(0x11);
+        let mut bloom = H2048::default();
+        bloom.accrue(BloomInput::Raw(address.as_bytes()));
+        bloom.accrue(BloomInput::Raw(topic.as_bytes()));
+
+        // then
+        let contains = |address| bloom.contains_address(&helpers::bytes_to_address("lax", address));
+        assert_eq!(contains(&address), Ok(true));
+        assert_eq!(contains(&H160::repeat_byte(0xa0)), Ok(false));
+        assert!(bloom.contains_topic(&topic));
+        assert!(!bloom.contains_topic(&H256::repeat_byte(0x22)));
+        assert!(matches!(
+            bloom.contains_address("not an address"),
+            Err(Error::Decoder(_))
+        ));
+    }
+}
@@ -1,4 +1,4 @@
-use crate::types::Address;
+use crate::types::{Address, Page};
 use serde::{Deserialize, Serialize};
 
 /// The contract type returned from contract calls.
@@ -14,3 +14,6 @@ pub struct Contract {
     /// Owner of the contract
     pub owner: Address,
 }
+
+/// The contract page info returned from contract calls.
+pub type ContractPage = Page<Contract>;
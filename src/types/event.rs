@@ -0,0 +1,117 @@
+use crate::{
+    error::{Error, Result},
+    types::{Address, Log, H256},
+};
+use std::collections::HashMap;
+
+/// Event decoded from a log.
+#[derive(Debug, Clone, PartialEq)]
+pub struct DecodedLog {
+    /// Name of the event
+    pub name: String,
+    /// Decoded parameters of the event
+    pub params: Vec<ethabi::LogParam>,
+    /// Address of the contract which emitted the event
+    pub address: Address,
+    /// Log index in block
+    pub log_index: Option<crate::types::U256>,
+}
+
+/// Decodes logs into events, given their ABI.
+///
+/// Logs are matched with the events by their first topic, anonymous events
+/// can't be matched and are ignored.
+#[derive(Debug, Clone, Default)]
+pub struct EventDecoder {
+    events: HashMap<H256, ethabi::Event>,
+}
+
+impl EventDecoder {
+    /// Create a decoder for the events of the given contract ABI.
+    pub fn new(abi: &ethabi::Contract) -> Self {
+        Self::default().events(abi.events().cloned())
+    }
+
+    /// Add the given events to the decoder.
+    pub fn events(mut self, events: impl IntoIterator<Item = ethabi::Event>) -> Self {
+        for event in events.into_iter().filter(|event| !event.anonymous) {
+            self.events.insert(event.signature(), event);
+        }
+        self
+    }
+
+    /// Decodes the log, returns `None` if it's not one of the known events.
+    pub fn decode(&self, log: &Log) -> Result<Option<DecodedLog>> {
+        let event = match log.topics.first().and_then(|topic| self.events.get(topic)) {
+            Some(event) => event,
+            None => return Ok(None),
+        };
+        let decoded = event
+            .parse_log(ethabi::RawLog {
+                topics: log.topics.clone(),
+                data: log.data.0.clone(),
+            })
+            .map_err(|err| Error::Decoder(format!("{}: {}", event.name, err)))?;
+        Ok(Some(DecodedLog {
+            name: event.name.clone(),
+            params: decoded.params,
+            address: log.address.clone(),
+            log_index: log.log_index,
+        }))
+    }
+}
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use ethabi::Token;
+
+    const ERC20: &[u8] = br#"[{"type":"event","name":"Transfer","anonymous":false,"inputs":[
+        {"name":"from","type":"address","indexed":true},
+        {"name":"to","type":"address","indexed":true},
+        {"name":"value","type":"uint256","indexed":false}]}]"#;
+
+    pub fn erc20() -> ethabi::Contract {
+        ethabi::Contract::load(ERC20).unwrap()
+    }
+
+    pub fn transfer_log(address: &str, value: u64) -> Log {
+        let abi = erc20();
+        let event = abi.event("Transfer").unwrap();
+        Log {
+            address: address.into(),
+            topics: vec![
+                event.signature(),
+                H256::from_low_u64_be(1),
+                H256::from_low_u64_be(2),
+            ],
+            data: ethabi::encode(&[Token::Uint(value.into())]).into(),
+            block_hash: None,
+            block_number: None,
+            transaction_hash: None,
+            transaction_index: None,
+            log_index: Some(3.into()),
+            transaction_log_index: None,
+            removed: None,
+        }
+    }
+
+    #[test]
+    fn should_decode_known_events() {
+        // given
+        let decoder = EventDecoder::new(&erc20());
+        let mut unknown = transfer_log("lax1token", 1);
+        unknown.topics[0] = H256::zero();
+
+        // when
+        let decoded = decoder.decode(&transfer_log("lax1token", 10)).unwrap();
+
+        // then
+        let decoded = decoded.unwrap();
+        assert_eq!(decoded.name, "Transfer");
+        assert_eq!(decoded.address, "lax1token");
+        assert_eq!(decoded.params[2].name, "value");
+        assert_eq!(decoded.params[2].value, Token::Uint(10.into()));
+        assert_eq!(decoder.decode(&unknown), Ok(None));
+    }
+}
@@ -1,3 +1,5 @@
+#![allow(unused_must_use)]
+
 use fixed_hash::*;
 
 use impl_codec::impl_fixed_hash_codec;
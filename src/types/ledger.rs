@@ -1,4 +1,4 @@
-use crate::types::{BlsPublic, Public};
+use crate::types::{BlsPublic, LedgerStatus, NodeType, Page, Public};
 use serde::{Deserialize, Serialize};
 
 /// The ledger type returned from contract calls.
@@ -19,7 +19,7 @@ pub struct Ledger {
     pub observe: Vec<CbftNode>,
     /// Status of the ledger
     #[serde(rename = "ledgerStatus")]
-    pub status: u8,
+    pub status: LedgerStatus,
 }
 
 /// The cbft node type returned from contract calls.
@@ -40,5 +40,8 @@ pub struct LedgerNode {
     pub pub_key: Public,
     /// Node type
     #[serde(rename = "nodeType")]
-    pub node_type: u8,
+    pub node_type: NodeType,
 }
+
+/// The ledger page info returned from contract calls.
+pub type LedgerPage = Page<Ledger>;
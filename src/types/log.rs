@@ -35,10 +35,6 @@ pub struct Log {
 impl Log {
     /// Returns true if the log has removed.
     pub fn is_removed(&self) -> bool {
-        match self.removed {
-            Some(val_removed) => return val_removed,
-            None => (),
-        }
-        false
+        self.removed.unwrap_or(false)
     }
 }
@@ -1,12 +1,21 @@
+mod admin;
 mod block;
+mod bloom;
 mod bytes;
 mod contract;
+mod event;
 mod h768;
 mod ledger;
 mod log;
 mod node;
+mod page;
 mod params;
+mod proof;
+mod revert;
+mod role;
+mod status;
 mod sync_state;
+mod trace;
 mod transaction;
 mod transaction_id;
 mod transaction_request;
@@ -24,20 +33,31 @@ pub type BlsPublic = H768;
 pub type Index = U64;
 
 pub use self::{
+    admin::{NodeInfo, PeerInfo, PeerNetwork, Ports},
     block::{Block, BlockHeader, BlockId, BlockNumber},
+    bloom::BloomExt,
     bytes::Bytes,
-    contract::Contract,
+    contract::{Contract, ContractPage},
+    event::{DecodedLog, EventDecoder},
     h768::H768,
-    ledger::{CbftNode, Ledger, LedgerNode},
+    ledger::{CbftNode, Ledger, LedgerNode, LedgerPage},
     log::Log,
-    node::Node,
+    node::{Node, NodePage},
+    page::Page,
     params::SysParams,
+    proof::{Proof, StorageProof},
+    revert::RevertReason,
+    role::{Role, Roles},
+    status::{LedgerStatus, NodeStatus, NodeType, UserStatus},
     sync_state::SyncState,
+    trace::{BadBlock, CallFrame},
     transaction_id::TransactionId,
     transaction_request::{CallRequest, TransactionRequest},
-    user::User,
+    user::{User, UserPage},
 };
 
-pub use self::transaction::{RawTransaction, Receipt as TransactionReceipt, Transaction};
+pub use self::transaction::{
+    RawTransaction, Receipt as TransactionReceipt, ReceiptStatus, Transaction,
+};
 
 pub type LedgerNodeList = Vec<ledger::LedgerNode>;
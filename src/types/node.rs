@@ -1,4 +1,4 @@
-use crate::types::{Address, BlsPublic, Public};
+use crate::types::{Address, BlsPublic, NodeStatus, NodeType, Page, Public};
 use serde::{Deserialize, Serialize};
 
 /// The node type returned from contract calls.
@@ -12,7 +12,7 @@ pub struct Node {
     pub desc: String,
     /// Node type
     #[serde(rename = "nodeType")]
-    pub node_type: u8,
+    pub node_type: NodeType,
     /// Public key
     #[serde(rename = "publicKey")]
     pub public_key: Public,
@@ -29,7 +29,7 @@ pub struct Node {
     #[serde(rename = "p2pPort")]
     pub p2p_port: u32,
     /// Node status
-    pub status: u8,
+    pub status: NodeStatus,
     /// Is root?
     pub root: bool,
     /// Create time
@@ -39,3 +39,36 @@ pub struct Node {
     #[serde(rename = "updateTime")]
     pub update_time: u64,
 }
+
+/// The node page info returned from contract calls.
+pub type NodePage = Page<Node>;
+
+#[cfg(test)]
+mod tests {
+    use super::Node;
+    use crate::types::{BlsPublic, NodeStatus, NodeType, Public};
+    use serde_json::json;
+
+    #[test]
+    fn should_deserialize_observer_node() {
+        let node: Node = serde_json::from_value(json!({
+            "name": "observer-1",
+            "owner": "lax18qg084alcnuv2jjdx4u68vw4ve8lffm0ncrzww",
+            "desc": "",
+            "nodeType": 0,
+            "publicKey": Public::repeat_byte(1),
+            "blsPubKey": BlsPublic::repeat_byte(2),
+            "hostAddress": "10.0.0.2",
+            "rpcPort": 6791,
+            "p2pPort": 16791,
+            "status": 1,
+            "root": false,
+            "createTime": 1600000000,
+            "updateTime": 1600000000
+        }))
+        .unwrap();
+
+        assert_eq!(node.node_type, NodeType::Observer);
+        assert_eq!(node.status, NodeStatus::Normal);
+    }
+}
@@ -0,0 +1,36 @@
+use serde::{Deserialize, Serialize};
+
+/// A page of items returned from paginated contract calls.
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Page<T> {
+    /// Total counts
+    #[serde(rename = "totalCount")]
+    pub total_count: u32,
+    /// Total pages
+    #[serde(rename = "totalPage")]
+    pub total_page: u32,
+    /// Page number, starting from 1
+    #[serde(rename = "pageNum")]
+    pub page_num: u32,
+    /// Page size
+    #[serde(rename = "pageSize")]
+    pub page_size: u32,
+    /// Items of this page
+    pub items: Vec<T>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Page;
+
+    #[test]
+    fn should_deserialize_page() {
+        let page: Page<u32> = serde_json::from_str(
+            r#"{"totalCount":3,"totalPage":2,"pageNum":1,"pageSize":2,"items":[1,2]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(page.page_size, 2);
+        assert_eq!(page.items, vec![1, 2]);
+    }
+}
@@ -0,0 +1,321 @@
+use crate::{
+    error::{Error, Result},
+    helpers, trie,
+    types::{Address, BlockHeader, Bytes, H256, U256, U64},
+};
+use rlp::RlpStream;
+use serde::{Deserialize, Serialize};
+
+/// Account and storage proofs returned by `juice_getProof`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Proof {
+    /// Address of the account
+    pub address: Address,
+    /// Balance of the account
+    pub balance: U256,
+    /// Hash of the code of the account
+    #[serde(rename = "codeHash")]
+    pub code_hash: H256,
+    /// Nonce of the account
+    pub nonce: U64,
+    /// Root of the storage trie of the account
+    #[serde(rename = "storageHash")]
+    pub storage_hash: H256,
+    /// Nodes of the state trie on the path of the account
+    #[serde(rename = "accountProof")]
+    pub account_proof: Vec<Bytes>,
+    /// Proofs of the requested storage keys
+    #[serde(rename = "storageProof")]
+    pub storage_proof: Vec<StorageProof>,
+}
+
+/// Proof of a storage slot of an account.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StorageProof {
+    /// Storage key
+    pub key: U256,
+    /// Value of the slot
+    pub value: U256,
+    /// Nodes of the storage trie on the path of the key
+    pub proof: Vec<Bytes>,
+}
+
+impl Proof {
+    /// Checks that the proof answers the requested account and storage keys,
+    /// then checks the account against the state root of the block it was
+    /// requested at and every storage slot against the account's storage
+    /// root.
+    ///
+    /// An account absent from the state must be returned with a zero nonce
+    /// and balance, an empty storage and no code. The account is expected to
+    /// be stored as `[nonce, balance, storage root, code hash]`, as in
+    /// Ethereum; this layout has not been checked against a node's
+    /// `juice_getProof` response yet.
+    pub fn verify(&self, address: &str, keys: &[H256], state_root: H256) -> Result {
+        let requested = helpers::address_to_bytes(address)?;
+        if helpers::address_to_bytes(&self.address)? != requested {
+            return Err(Error::Verification(format!(
+                "proof is for account {}, not {}",
+                self.address, address
+            )));
+        }
+        let proved = self
+            .storage_proof
+            .iter()
+            .map(|proof| H256::from(<[u8; 32]>::from(proof.key)));
+        if !proved.eq(keys.iter().copied()) {
+            return Err(Error::Verification(format!(
+                "storage proofs of {} do not match the requested keys",
+                address
+            )));
+        }
+
+        let key = helpers::keccak256(requested.as_bytes());
+        match trie::verify_proof(state_root, &key, &self.account_proof)? {
+            Some(account) if account != self.account_rlp() => {
+                return Err(Error::Verification(format!(
+                    "account {} does not match its proof",
+                    address
+                )))
+            }
+            None if !self.is_empty_account() => {
+                return Err(Error::Verification(format!(
+                    "account {} is absent from the state",
+                    address
+                )))
+            }
+            _ => {}
+        }
+        self.storage_proof
+            .iter()
+            .try_for_each(|proof| proof.verify(self.storage_hash))
+    }
+
+    /// Same as `verify`, against the state root of the block header.
+    ///
+    /// The header itself is not checked: it must have passed
+    /// `BlockHeader::verify_hash` against a trusted block hash, otherwise any
+    /// state root could be proved.
+    pub fn verify_header(&self, address: &str, keys: &[H256], header: &BlockHeader) -> Result {
+        self.verify(address, keys, header.root)
+    }
+
+    fn is_empty_account(&self) -> bool {
+        self.nonce.is_zero()
+            && self.balance.is_zero()
+            && self.storage_hash == trie::EMPTY_ROOT
+            && self.code_hash == H256(helpers::keccak256(&[]))
+    }
+
+    fn account_rlp(&self) -> Vec<u8> {
+        let mut stream = RlpStream::new_list(4);
+        stream
+            .append(&self.nonce)
+            .append(&self.balance)
+            .append(&self.storage_hash)
+            .append(&self.code_hash);
+        stream.out().to_vec()
+    }
+}
+
+impl StorageProof {
+    /// Checks the value of the slot against the storage root of the account.
+    pub fn verify(&self, storage_root: H256) -> Result {
+        let key = helpers::keccak256(&<[u8; 32]>::from(self.key));
+        let value = trie::verify_proof(storage_root, &key, &self.proof)?;
+        // Zero slots are deleted from the trie.
+        let expected = Some(rlp::encode(&self.value).to_vec()).filter(|_| !self.value.is_zero());
+        if value != expected {
+            return Err(Error::Verification(format!(
+                "storage slot {:#x} does not match its proof",
+                self.key
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{trie::TrieBuilder, types::H160};
+    use serde_json::json;
+
+    fn account(storage_hash: H256) -> Proof {
+        Proof {
+            address: helpers::bytes_to_address("lax", &H160::repeat_byte(7)),
+            balance: 1_000.into(),
+            code_hash: H256(helpers::keccak256(&[])),
+            nonce: 3.into(),
+            storage_hash,
+            account_proof: vec![],
+            storage_proof: vec![],
+        }
+    }
+
+    fn slot(key: u64) -> [u8; 32] {
+        helpers::keccak256(&<[u8; 32]>::from(U256::from(key)))
+    }
+
+    fn proved_account() -> (Proof, H256) {
+        let storage = TrieBuilder::new()
+            .insert(slot(0), rlp::encode(&U256::from(42)))
+            .insert(slot(1), rlp::encode(&U256::from(43)));
+        let mut proof = account(storage.root());
+        let key = helpers::keccak256(H160::repeat_byte(7).as_bytes());
+        let state = (0u8..20)
+            .fold(TrieBuilder::new(), |trie, idx| {
+                let address = H160::repeat_byte(0x80 + idx);
+                trie.insert(helpers::keccak256(address.as_bytes()), [idx])
+            })
+            .insert(key, proof.account_rlp());
+        proof.account_proof = state.proof(key);
+        proof.storage_proof = vec![
+            StorageProof {
+                key: 1.into(),
+                value: 43.into(),
+                proof: storage.proof(slot(1)),
+            },
+            StorageProof {
+                key: 2.into(),
+                value: 0.into(),
+                proof: storage.proof(slot(2)),
+            },
+        ];
+        (proof, state.root())
+    }
+
+    fn keys() -> Vec<H256> {
+        vec![H256::from_low_u64_be(1), H256::from_low_u64_be(2)]
+    }
+
+    #[test]
+    fn should_verify_account_and_storage() {
+        // given
+        let (proof, root) = proved_account();
+        let address = proof.address.clone();
+
+        // then
+        assert_eq!(proof.verify(&address, &keys(), root), Ok(()));
+        let mut forged = proof.clone();
+        forged.balance += U256::one();
+        assert!(matches!(
+            forged.verify(&address, &keys(), root),
+            Err(Error::Verification(_))
+        ));
+        let mut forged = proof.clone();
+        forged.storage_proof[1].value = 1.into();
+        assert!(forged.verify(&address, &keys(), root).is_err());
+    }
+
+    #[test]
+    fn should_verify_against_header() {
+        // given
+        let (proof, root) = proved_account();
+        let address = proof.address.clone();
+        let mut header: BlockHeader = serde_json::from_value(json!({
+            "parentHash": H256::zero(),
+            "miner": helpers::bytes_to_address("lax", &H160::zero()),
+            "stateRoot": root,
+            "transactionsRoot": trie::EMPTY_ROOT,
+            "receiptsRoot": trie::EMPTY_ROOT,
+            "number": "0x1",
+            "gasLimit": "0x0",
+            "gasUsed": "0x0",
+            "timestamp": "0x0",
+            "extraData": "0x",
+            "nonce": "0x",
+            "hash": H256::zero(),
+        }))
+        .unwrap();
+
+        // then
+        assert_eq!(proof.verify_header(&address, &keys(), &header), Ok(()));
+        header.root = trie::EMPTY_ROOT;
+        assert!(matches!(
+            proof.verify_header(&address, &keys(), &header),
+            Err(Error::Verification(_))
+        ));
+    }
+
+    #[test]
+    fn should_reject_proof_of_another_account() {
+        // given
+        let (proof, root) = proved_account();
+        let other = helpers::bytes_to_address("lax", &H160::repeat_byte(8));
+
+        // then
+        assert!(matches!(
+            proof.verify(&other, &keys(), root),
+            Err(Error::Verification(_))
+        ));
+    }
+
+    #[test]
+    fn should_reject_proof_of_other_keys() {
+        // given
+        let (proof, root) = proved_account();
+        let address = proof.address.clone();
+
+        // then
+        let swapped = vec![H256::from_low_u64_be(1), H256::from_low_u64_be(0)];
+        assert!(matches!(
+            proof.verify(&address, &swapped, root),
+            Err(Error::Verification(_))
+        ));
+        assert!(proof.verify(&address, &keys()[..1], root).is_err());
+    }
+
+    #[test]
+    fn should_verify_absent_account() {
+        // given
+        let key = helpers::keccak256(H160::repeat_byte(7).as_bytes());
+        let state = TrieBuilder::new().insert([1], [1]);
+        let mut absent = account(trie::EMPTY_ROOT);
+        absent.account_proof = state.proof(key);
+        let address = absent.address.clone();
+
+        // then
+        assert!(absent.verify(&address, &[], state.root()).is_err());
+        absent.nonce = 0.into();
+        absent.balance = 0.into();
+        assert_eq!(absent.verify(&address, &[], state.root()), Ok(()));
+        // faked storage on an absent account
+        let storage = TrieBuilder::new().insert(slot(1), rlp::encode(&U256::from(43)));
+        let mut faked = absent.clone();
+        faked.storage_hash = storage.root();
+        faked.storage_proof = vec![StorageProof {
+            key: 1.into(),
+            value: 43.into(),
+            proof: storage.proof(slot(1)),
+        }];
+        assert!(matches!(
+            faked.verify(&address, &keys()[..1], state.root()),
+            Err(Error::Verification(_))
+        ));
+        let mut faked = absent.clone();
+        faked.code_hash = H256::repeat_byte(1);
+        assert!(faked.verify(&address, &[], state.root()).is_err());
+    }
+
+    #[test]
+    fn should_deserialize_proof() {
+        let proof: Proof = serde_json::from_value(json!({
+            "address": "lax18qg084alcnuv2jjdx4u68vw4ve8lffm0ncrzww",
+            "balance": "0x0",
+            "codeHash": H256::zero(),
+            "nonce": "0x0",
+            "storageHash": trie::EMPTY_ROOT,
+            "accountProof": ["0x80"],
+            "storageProof": [{
+                "key": "0x0000000000000000000000000000000000000000000000000000000000000001",
+                "value": "0x0",
+                "proof": []
+            }]
+        }))
+        .unwrap();
+
+        assert_eq!(proof.storage_proof[0].key, 1.into());
+        assert_eq!(proof.account_proof, vec![Bytes(vec![0x80])]);
+    }
+}
@@ -0,0 +1,170 @@
+use crate::types::{Bytes, U256};
+use ethabi::{ParamType, Token};
+use std::fmt;
+
+/// Selector of `Error(string)`.
+const ERROR_SELECTOR: [u8; 4] = [0x08, 0xc3, 0x79, 0xa0];
+/// Selector of `Panic(uint256)`.
+const PANIC_SELECTOR: [u8; 4] = [0x4e, 0x48, 0x7b, 0x71];
+
+/// Reason of a reverted execution, decoded from the revert data.
+#[derive(Debug, Clone, PartialEq)]
+pub enum RevertReason {
+    /// `revert("reason")` or a failed `require(cond, "reason")`
+    Error(String),
+    /// Failed `assert`, arithmetic overflow, division by zero...
+    Panic(U256),
+    /// Custom error declared in the contract ABI
+    Custom {
+        /// Name of the error
+        name: String,
+        /// Decoded parameters of the error
+        params: Vec<Token>,
+    },
+    /// Revert data which could not be decoded
+    Unknown(Bytes),
+}
+
+impl RevertReason {
+    /// Decodes `Error(string)` and `Panic(uint256)` revert data.
+    pub fn decode(data: &[u8]) -> Self {
+        let (selector, payload) = match data.len() {
+            len if len >= 4 => data.split_at(4),
+            _ => return RevertReason::Unknown(data.into()),
+        };
+        let decoded = if selector == ERROR_SELECTOR {
+            ethabi::decode(&[ParamType::String], payload)
+                .ok()
+                .and_then(|tokens| tokens.into_iter().next()?.into_string())
+                .map(RevertReason::Error)
+        } else if selector == PANIC_SELECTOR {
+            ethabi::decode(&[ParamType::Uint(256)], payload)
+                .ok()
+                .and_then(|tokens| tokens.into_iter().next()?.into_uint())
+                .map(RevertReason::Panic)
+        } else {
+            None
+        };
+        decoded.unwrap_or_else(|| RevertReason::Unknown(data.into()))
+    }
+
+    /// Decodes revert data, including the custom errors of the given ABI.
+    pub fn decode_with_abi(data: &[u8], abi: &ethabi::Contract) -> Self {
+        let reason = Self::decode(data);
+        if !matches!(reason, RevertReason::Unknown(_)) {
+            return reason;
+        }
+        abi.errors()
+            .find(|error| data.len() >= 4 && error.signature()[..4] == data[..4])
+            .and_then(|error| {
+                let params = error.decode(&data[4..]).ok()?;
+                Some(RevertReason::Custom {
+                    name: error.name.clone(),
+                    params,
+                })
+            })
+            .unwrap_or(reason)
+    }
+
+    /// Returns the meaning of a `Panic` code, as defined by Solidity.
+    pub fn panic_description(&self) -> Option<&'static str> {
+        let code = match self {
+            RevertReason::Panic(code) if code.bits() <= 8 => code.low_u32(),
+            _ => return None,
+        };
+        let description = match code {
+            0x00 => "generic compiler panic",
+            0x01 => "assertion failed",
+            0x11 => "arithmetic overflow or underflow",
+            0x12 => "division or modulo by zero",
+            0x21 => "invalid enum value",
+            0x22 => "invalid storage byte array encoding",
+            0x31 => "pop on empty array",
+            0x32 => "array index out of bounds",
+            0x41 => "out of memory",
+            0x51 => "call to uninitialized function",
+            _ => return None,
+        };
+        Some(description)
+    }
+}
+
+impl fmt::Display for RevertReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RevertReason::Error(reason) => write!(f, "{}", reason),
+            RevertReason::Panic(code) => match self.panic_description() {
+                Some(description) => write!(f, "Panic(0x{:x}): {}", code, description),
+                None => write!(f, "Panic(0x{:x})", code),
+            },
+            RevertReason::Custom { name, params } => {
+                let params = params.iter().map(Token::to_string).collect::<Vec<_>>();
+                write!(f, "{}({})", name, params.join(", "))
+            }
+            RevertReason::Unknown(data) => write!(f, "0x{}", hex::encode(&data.0)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode(selector: [u8; 4], tokens: &[Token]) -> Vec<u8> {
+        [selector.to_vec(), ethabi::encode(tokens)].concat()
+    }
+
+    #[test]
+    fn should_decode_error_string() {
+        let data = encode(ERROR_SELECTOR, &[Token::String("not owner".into())]);
+
+        let reason = RevertReason::decode(&data);
+
+        assert_eq!(reason, RevertReason::Error("not owner".into()));
+        assert_eq!(reason.to_string(), "not owner");
+    }
+
+    #[test]
+    fn should_decode_panic() {
+        let data = encode(PANIC_SELECTOR, &[Token::Uint(0x11.into())]);
+
+        let reason = RevertReason::decode(&data);
+
+        assert_eq!(reason, RevertReason::Panic(0x11.into()));
+        assert_eq!(
+            reason.to_string(),
+            "Panic(0x11): arithmetic overflow or underflow"
+        );
+    }
+
+    #[test]
+    fn should_decode_custom_errors() {
+        // given
+        let abi = ethabi::Contract::load(
+            br#"[{"type":"error","name":"InsufficientBalance","inputs":[
+                {"name":"available","type":"uint256"},{"name":"required","type":"uint256"}]}]"#
+                as &[u8],
+        )
+        .unwrap();
+        let error = abi.error("InsufficientBalance").unwrap();
+        let data = error
+            .encode(&[Token::Uint(1.into()), Token::Uint(2.into())])
+            .unwrap();
+
+        // when
+        let reason = RevertReason::decode_with_abi(&data, &abi);
+
+        // then
+        assert_eq!(
+            reason,
+            RevertReason::Custom {
+                name: "InsufficientBalance".into(),
+                params: vec![Token::Uint(1.into()), Token::Uint(2.into())],
+            }
+        );
+        assert_eq!(
+            RevertReason::decode(&data),
+            RevertReason::Unknown(data.into())
+        );
+    }
+}
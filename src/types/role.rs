@@ -0,0 +1,196 @@
+use crate::types::U64;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::{fmt, ops};
+
+/// A single user role known to the chain.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Role {
+    /// Creator of the chain
+    ChainCreator,
+    /// Chain administrator
+    ChainAdmin,
+    /// Node administrator
+    NodeAdmin,
+    /// Contract administrator
+    ContractAdmin,
+    /// Allowed to deploy contracts
+    ContractDeployer,
+}
+
+impl Role {
+    /// All known roles, in bit order.
+    pub const ALL: [Role; 5] = [
+        Role::ChainCreator,
+        Role::ChainAdmin,
+        Role::NodeAdmin,
+        Role::ContractAdmin,
+        Role::ContractDeployer,
+    ];
+
+    /// Returns the bit of this role inside `User.roles`.
+    pub fn bit(self) -> u64 {
+        match self {
+            Role::ChainCreator => 1 << 0,
+            Role::ChainAdmin => 1 << 1,
+            Role::NodeAdmin => 1 << 2,
+            Role::ContractAdmin => 1 << 3,
+            Role::ContractDeployer => 1 << 4,
+        }
+    }
+}
+
+/// Role bitmask of a user.
+///
+/// Serialized as the hex encoded `U64` the system contracts return. Bits
+/// which don't map to a known `Role` are preserved.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct Roles(u64);
+
+impl Roles {
+    /// No roles at all.
+    pub const fn empty() -> Self {
+        Roles(0)
+    }
+
+    /// Create a bitmask from raw bits.
+    pub const fn from_bits(bits: u64) -> Self {
+        Roles(bits)
+    }
+
+    /// Returns the raw bits.
+    pub const fn bits(&self) -> u64 {
+        self.0
+    }
+
+    /// Returns true if no bit is set.
+    pub fn is_empty(&self) -> bool {
+        self.0 == 0
+    }
+
+    /// Returns true if the given role is set.
+    pub fn has(&self, role: Role) -> bool {
+        self.0 & role.bit() != 0
+    }
+
+    /// Returns true if every role in `other` is set.
+    pub fn contains(&self, other: Roles) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns true if at least one role in `other` is set.
+    pub fn intersects(&self, other: Roles) -> bool {
+        self.0 & other.0 != 0
+    }
+
+    /// Set the given role.
+    pub fn insert(&mut self, role: Role) {
+        self.0 |= role.bit();
+    }
+
+    /// Clear the given role.
+    pub fn remove(&mut self, role: Role) {
+        self.0 &= !role.bit();
+    }
+
+    /// Returns the known roles which are set.
+    pub fn iter(&self) -> impl Iterator<Item = Role> + '_ {
+        Role::ALL
+            .iter()
+            .copied()
+            .filter(move |role| self.has(*role))
+    }
+}
+
+impl From<Role> for Roles {
+    fn from(role: Role) -> Self {
+        Roles(role.bit())
+    }
+}
+
+impl From<U64> for Roles {
+    fn from(bits: U64) -> Self {
+        Roles(bits.as_u64())
+    }
+}
+
+impl From<Roles> for U64 {
+    fn from(roles: Roles) -> Self {
+        U64::from(roles.0)
+    }
+}
+
+impl<T: Into<Roles>> ops::BitOr<T> for Roles {
+    type Output = Roles;
+
+    fn bitor(self, other: T) -> Roles {
+        Roles(self.0 | other.into().0)
+    }
+}
+
+impl ops::BitOr for Role {
+    type Output = Roles;
+
+    fn bitor(self, other: Role) -> Roles {
+        Roles::from(self) | other
+    }
+}
+
+impl<T: Into<Roles>> ops::BitOrAssign<T> for Roles {
+    fn bitor_assign(&mut self, other: T) {
+        self.0 |= other.into().0;
+    }
+}
+
+impl fmt::Display for Roles {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:#x}", self.0)
+    }
+}
+
+impl Serialize for Roles {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        U64::from(*self).serialize(serializer)
+    }
+}
+
+impl<'de> Deserialize<'de> for Roles {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        U64::deserialize(deserializer).map(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Role, Roles};
+
+    #[test]
+    fn should_check_roles() {
+        let roles = Role::ChainAdmin | Role::ContractDeployer;
+
+        assert!(roles.has(Role::ContractDeployer));
+        assert!(!roles.has(Role::NodeAdmin));
+        assert!(roles.contains(Role::ChainAdmin.into()));
+        assert!(!roles.contains(Role::ChainAdmin | Role::NodeAdmin));
+        assert!(roles.intersects(Role::ChainAdmin | Role::NodeAdmin));
+        assert_eq!(
+            roles.iter().collect::<Vec<_>>(),
+            vec![Role::ChainAdmin, Role::ContractDeployer]
+        );
+    }
+
+    #[test]
+    fn should_round_trip_as_hex_u64() {
+        let roles: Roles = serde_json::from_str(r#""0x112""#).unwrap();
+
+        assert!(roles.has(Role::ChainAdmin));
+        assert!(roles.has(Role::ContractDeployer));
+        assert_eq!(roles.bits(), 0x112);
+        assert_eq!(serde_json::to_string(&roles).unwrap(), r#""0x112""#);
+    }
+}
@@ -0,0 +1,125 @@
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Declares a `u8` backed enum which keeps unknown values in `Unknown`.
+macro_rules! u8_enum {
+    (
+        $(#[$meta: meta])*
+        pub enum $name: ident {
+            $($(#[$vmeta: meta])* $variant: ident = $value: expr,)+
+        }
+    ) => {
+        $(#[$meta])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+        pub enum $name {
+            $($(#[$vmeta])* $variant,)+
+            /// Value not known to this SDK
+            Unknown(u8),
+        }
+
+        impl From<u8> for $name {
+            fn from(value: u8) -> Self {
+                match value {
+                    $($value => $name::$variant,)+
+                    other => $name::Unknown(other),
+                }
+            }
+        }
+
+        impl From<$name> for u8 {
+            fn from(value: $name) -> Self {
+                match value {
+                    $($name::$variant => $value,)+
+                    $name::Unknown(other) => other,
+                }
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: Serializer,
+            {
+                serializer.serialize_u8((*self).into())
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                u8::deserialize(deserializer).map(Into::into)
+            }
+        }
+    };
+}
+
+u8_enum! {
+    /// Type of a node, as stored by the node manager system contract.
+    pub enum NodeType {
+        /// Only follows the chain
+        Observer = 0,
+        /// Takes part in consensus
+        Consensus = 1,
+    }
+}
+
+u8_enum! {
+    /// Status of a node, as stored by the node manager system contract.
+    pub enum NodeStatus {
+        /// Node is usable
+        Normal = 1,
+        /// Node is deleted
+        Deleted = 2,
+    }
+}
+
+u8_enum! {
+    /// Status of a ledger.
+    pub enum LedgerStatus {
+        /// Ledger is running
+        Active = 1,
+        /// Ledger is frozen
+        Frozen = 2,
+        /// Ledger is deleted
+        Deleted = 3,
+    }
+}
+
+u8_enum! {
+    /// Status of a user.
+    pub enum UserStatus {
+        /// User is usable
+        Normal = 1,
+        /// User is frozen
+        Frozen = 2,
+        /// User is deleted
+        Deleted = 3,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{LedgerStatus, NodeStatus, NodeType};
+
+    #[test]
+    fn should_map_known_values() {
+        let node_type: NodeType = serde_json::from_str("1").unwrap();
+        assert_eq!(node_type, NodeType::Consensus);
+        let node_type: NodeType = serde_json::from_str("0").unwrap();
+        assert_eq!(node_type, NodeType::Observer);
+        let status: NodeStatus = serde_json::from_str("2").unwrap();
+        assert_eq!(status, NodeStatus::Deleted);
+
+        let status: LedgerStatus = serde_json::from_str("2").unwrap();
+        assert_eq!(status, LedgerStatus::Frozen);
+        assert_eq!(serde_json::to_string(&status).unwrap(), "2");
+    }
+
+    #[test]
+    fn should_keep_unknown_values() {
+        let status: LedgerStatus = serde_json::from_str("9").unwrap();
+        assert_eq!(status, LedgerStatus::Unknown(9));
+        assert_eq!(serde_json::to_string(&status).unwrap(), "9");
+    }
+}
@@ -0,0 +1,72 @@
+use crate::types::{Address, Block, Bytes, Transaction, H256, U256};
+use serde::{Deserialize, Serialize};
+
+/// Call frame of a transaction trace, built by the `callTracer`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CallFrame {
+    /// Type of the call: `CALL`, `DELEGATECALL`, `CREATE`...
+    #[serde(rename = "type")]
+    pub call_type: String,
+    /// Caller
+    pub from: Address,
+    /// Callee, `None` if the contract creation failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to: Option<Address>,
+    /// Transferred value
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub value: Option<U256>,
+    /// Gas available to the call
+    pub gas: U256,
+    /// Gas used by the call
+    #[serde(rename = "gasUsed")]
+    pub gas_used: U256,
+    /// Input data
+    pub input: Bytes,
+    /// Output data
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub output: Option<Bytes>,
+    /// Error, if the call failed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Decoded revert reason, if the call reverted
+    #[serde(
+        rename = "revertReason",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub revert_reason: Option<String>,
+    /// Calls made by the call
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub calls: Vec<CallFrame>,
+}
+
+impl CallFrame {
+    /// Returns true if the call failed.
+    pub fn is_failed(&self) -> bool {
+        self.error.is_some()
+    }
+
+    /// Returns the failed calls of the trace, depth first.
+    pub fn failed_calls(&self) -> Vec<&CallFrame> {
+        let mut failed = vec![];
+        let mut stack = vec![self];
+        while let Some(frame) = stack.pop() {
+            if frame.is_failed() {
+                failed.push(frame);
+            }
+            stack.extend(frame.calls.iter().rev());
+        }
+        failed
+    }
+}
+
+/// Block rejected by the node, returned by `debug_getBadBlocks`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BadBlock {
+    /// Hash of the block
+    pub hash: H256,
+    /// Decoded block
+    pub block: Block<Transaction>,
+    /// RLP encoded block
+    pub rlp: Bytes,
+}
@@ -1,5 +1,9 @@
-use crate::types::{Address, Bytes, Index, Log, H2048, H256, U256, U64};
-use serde::{Deserialize, Serialize};
+use crate::{
+    error::{Error, Result},
+    helpers,
+    types::{Address, Bytes, DecodedLog, EventDecoder, Index, Log, H2048, H256, U256, U64},
+};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 /// Description of a Transaction, pending or in the chain.
 #[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
@@ -22,7 +26,7 @@ pub struct Transaction {
     pub from: Option<Address>,
     /// Recipient (None when contract creation)
     pub to: Option<Address>,
-    /// Transfered value
+    /// Transferred value
     pub value: Option<U256>,
     /// Gas price
     #[serde(rename = "gasPrice")]
@@ -45,11 +49,48 @@ pub struct Transaction {
     pub raw: Option<Bytes>,
 }
 
+impl Transaction {
+    /// Encodes the signed transaction with RLP, as included in the
+    /// transactions trie of its block.
+    ///
+    /// Uses the raw transaction when known, fails if the signature is
+    /// missing.
+    pub fn rlp_bytes(&self) -> Result<Vec<u8>> {
+        if let Some(ref raw) = self.raw {
+            return Ok(raw.0.clone());
+        }
+        let missing = |field| {
+            Error::InvalidResponse(format!("transaction {:?} without {}", self.hash, field))
+        };
+        let to = self
+            .to
+            .as_deref()
+            .map(helpers::address_to_bytes)
+            .transpose()?;
+        let mut stream = rlp::RlpStream::new_list(9);
+        stream
+            .append(&self.nonce.ok_or_else(|| missing("nonce"))?)
+            .append(&self.gas_price)
+            .append(&self.gas);
+        match to {
+            Some(ref to) => stream.append(to),
+            None => stream.append_empty_data(),
+        };
+        stream
+            .append(&self.value.unwrap_or_default())
+            .append(&self.input.0)
+            .append(&self.v.ok_or_else(|| missing("v"))?)
+            .append(&self.r.ok_or_else(|| missing("r"))?)
+            .append(&self.s.ok_or_else(|| missing("s"))?);
+        Ok(stream.out().to_vec())
+    }
+}
+
 /// "Receipt" of an executed transaction: details of its execution.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct Receipt {
     /// Transaction hash.
-    #[serde(rename="transactionHash")]
+    #[serde(rename = "transactionHash")]
     pub transaction_hash: H256,
     /// Index within the block.
     #[serde(rename = "transactionIndex")]
@@ -68,11 +109,11 @@ pub struct Receipt {
     pub gas_used: Option<U256>,
     /// Contract address created, or `None` if not deployment.
     #[serde(rename = "contractAddress")]
-    pub contrace_address: Option<Address>,
+    pub contract_address: Option<Address>,
     /// Logs generated within this transaction.
     pub logs: Vec<Log>,
     /// Status: either 1 (success) or 0 (failure).
-    pub status: Option<U64>,
+    pub status: Option<ReceiptStatus>,
     /// State root
     pub root: Option<H256>,
     /// Logs bloom
@@ -80,6 +121,121 @@ pub struct Receipt {
     pub logs_bloom: H2048,
 }
 
+impl Receipt {
+    /// Returns true if the transaction was executed successfully.
+    pub fn is_success(&self) -> bool {
+        self.status == Some(ReceiptStatus::Success)
+    }
+
+    /// Returns the fee paid for the transaction at the given gas price, or
+    /// `None` if the gas used is unknown.
+    pub fn fee(&self, gas_price: U256) -> Option<U256> {
+        self.gas_used?.checked_mul(gas_price)
+    }
+
+    /// Returns the address of the created contract, if any.
+    pub fn contract_address(&self) -> Option<&Address> {
+        self.contract_address.as_ref()
+    }
+
+    /// Returns the logs emitted by the given address.
+    pub fn logs_for(&self, address: &str) -> Vec<&Log> {
+        self.logs
+            .iter()
+            .filter(|log| log.address == address)
+            .collect()
+    }
+
+    /// Encodes the receipt with RLP, as included in the receipts trie of its
+    /// block.
+    pub fn rlp_bytes(&self) -> Result<Vec<u8>> {
+        let mut stream = rlp::RlpStream::new_list(4);
+        match (self.root, self.status) {
+            (Some(ref root), _) => stream.append(root),
+            (None, Some(ReceiptStatus::Failure)) => stream.append_empty_data(),
+            (None, Some(status)) => stream.append(&U64::from(status)),
+            (None, None) => {
+                return Err(Error::InvalidResponse(format!(
+                    "receipt {:?} without status",
+                    self.transaction_hash
+                )))
+            }
+        };
+        stream
+            .append(&self.cumulative_gas_used)
+            .append(&self.logs_bloom)
+            .begin_list(self.logs.len());
+        for log in &self.logs {
+            let address = helpers::address_to_bytes(&log.address)?;
+            stream
+                .begin_list(3)
+                .append(&address)
+                .append_list(&log.topics)
+                .append(&log.data.0);
+        }
+        Ok(stream.out().to_vec())
+    }
+
+    /// Decodes the logs of the events known by the decoder, in order.
+    ///
+    /// Logs of unknown events are skipped.
+    pub fn decode_logs(&self, decoder: &EventDecoder) -> Result<Vec<DecodedLog>> {
+        self.logs
+            .iter()
+            .filter_map(|log| decoder.decode(log).transpose())
+            .collect()
+    }
+}
+
+/// Status of an executed transaction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ReceiptStatus {
+    /// The transaction failed, its state changes were reverted
+    Failure,
+    /// The transaction succeeded
+    Success,
+    /// Status not known by this version of the SDK
+    Unknown(U64),
+}
+
+impl From<U64> for ReceiptStatus {
+    fn from(status: U64) -> Self {
+        match status.as_u64() {
+            0 => ReceiptStatus::Failure,
+            1 => ReceiptStatus::Success,
+            _ => ReceiptStatus::Unknown(status),
+        }
+    }
+}
+
+impl From<ReceiptStatus> for U64 {
+    fn from(status: ReceiptStatus) -> Self {
+        match status {
+            ReceiptStatus::Failure => U64::zero(),
+            ReceiptStatus::Success => U64::one(),
+            ReceiptStatus::Unknown(status) => status,
+        }
+    }
+}
+
+impl Serialize for ReceiptStatus {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        U64::from(*self).serialize(serializer)
+    }
+}
+
+impl<'a> Deserialize<'a> for ReceiptStatus {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<ReceiptStatus, D::Error>
+    where
+        D: Deserializer<'a>,
+    {
+        U64::deserialize(deserializer).map(Into::into)
+    }
+}
+
 /// Raw bytes of a signed, but not yet sent transaction.
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct RawTransaction {
@@ -109,7 +265,7 @@ pub struct RawTransactionDetails {
     pub from: Option<Address>,
     /// Recipient (None when contract creation)
     pub to: Option<Address>,
-    /// Transfered value
+    /// Transferred value
     pub value: U256,
     /// Gas price
     #[serde(rename = "gasPrice")]
@@ -125,3 +281,105 @@ pub struct RawTransactionDetails {
     /// ECDSA signature s, 32 bytes, set by Juice
     pub s: Option<U256>,
 }
+
+#[cfg(test)]
+pub mod tests {
+    use super::*;
+    use crate::types::event::tests::{erc20, transfer_log};
+    use serde_json::json;
+
+    pub fn receipt(logs: Vec<Log>) -> Receipt {
+        serde_json::from_value(json!({
+            "transactionHash": H256::zero(),
+            "transactionIndex": "0x0",
+            "blockHash": null,
+            "blockNumber": "0x1",
+            "cumulativeGasUsed": "0x5208",
+            "gasUsed": "0x5208",
+            "contractAddress": "lax1contract",
+            "logs": logs,
+            "status": "0x1",
+            "root": null,
+            "logsBloom": H2048::zero(),
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn should_serialize_status() {
+        let receipt = receipt(vec![]);
+
+        assert_eq!(receipt.status, Some(ReceiptStatus::Success));
+        assert!(receipt.is_success());
+        assert_eq!(
+            serde_json::to_value(&receipt).unwrap()["status"],
+            json!("0x1")
+        );
+        assert_eq!(
+            serde_json::from_value::<ReceiptStatus>(json!("0x0")).unwrap(),
+            ReceiptStatus::Failure
+        );
+        assert_eq!(
+            serde_json::from_value::<ReceiptStatus>(json!("0x7")).unwrap(),
+            ReceiptStatus::Unknown(7.into())
+        );
+    }
+
+    #[test]
+    fn should_post_process_receipt() {
+        // given
+        let receipt = receipt(vec![
+            transfer_log("lax1token", 10),
+            transfer_log("lax1other", 20),
+        ]);
+        let decoder = EventDecoder::new(&erc20());
+
+        // when
+        let decoded = receipt.decode_logs(&decoder).unwrap();
+
+        // then
+        assert_eq!(receipt.fee(2.into()), Some(42_000.into()));
+        assert_eq!(
+            receipt.contract_address().map(String::as_str),
+            Some("lax1contract")
+        );
+        assert_eq!(receipt.logs_for("lax1other"), vec![&receipt.logs[1]]);
+        assert_eq!(decoded.len(), 2);
+        assert_eq!(decoded[1].address, "lax1other");
+    }
+
+    pub fn signed_transaction() -> Transaction {
+        // EIP-155 example transaction
+        let to = helpers::bytes_to_address("lax", &crate::types::H160::repeat_byte(0x35));
+        serde_json::from_value(json!({
+            "hash": H256::zero(),
+            "nonce": "0x9",
+            "blockHash": null,
+            "blockNumber": null,
+            "transactionIndex": null,
+            "to": to,
+            "value": "0xde0b6b3a7640000",
+            "gasPrice": "0x4a817c800",
+            "gas": "0x5208",
+            "input": "0x",
+            "v": "0x25",
+            "r": "0x28ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276",
+            "s": "0x67cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83"
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn should_encode_signed_transaction() {
+        let mut tx = signed_transaction();
+
+        assert_eq!(
+            hex::encode(tx.rlp_bytes().unwrap()),
+            "f86c098504a817c800825208943535353535353535353535353535353535353535880de0b6b3a76400008025a028ef61340bd939bc2195fe537567866003e1a15d3c71ff63e1590620aa636276a067cbe9d8997f761aecb703304b3800ccf555c9f3dc64214b297fb1966a3b6d83"
+        );
+        tx.v = None;
+        assert!(matches!(tx.rlp_bytes(), Err(Error::InvalidResponse(_))));
+        tx.raw = Some(vec![1, 2, 3].into());
+        assert_eq!(tx.rlp_bytes().unwrap(), vec![1, 2, 3]);
+    }
+}
@@ -18,9 +18,9 @@ pub struct CallRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub gas: Option<U256>,
     /// Gas price (None for sensible default)
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "gasPrice", skip_serializing_if = "Option::is_none")]
     pub gas_price: Option<U256>,
-    /// Transfered value (None for no transfer)
+    /// Transferred value (None for no transfer)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub value: Option<U256>,
     /// Data (None for empty data)
@@ -40,9 +40,9 @@ pub struct TransactionRequest {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub gas: Option<U256>,
     /// Gas price (None for sensible default)
-    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "gasPrice", skip_serializing_if = "Option::is_none")]
     pub gas_price: Option<U256>,
-    /// Transfered value (None for no transfer)
+    /// Transferred value (None for no transfer)
     pub value: Option<U256>,
     /// Transaction data (None for empty bytes)
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -50,3 +50,35 @@ pub struct TransactionRequest {
     /// Transaction nonce (None for next available nonce)
     pub nonce: Option<U256>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{CallRequest, TransactionRequest};
+    use serde_json::json;
+
+    #[test]
+    fn should_serialize_gas_price_in_camel_case() {
+        let call = CallRequest {
+            from: None,
+            to: Some("lax1contract".into()),
+            gas: None,
+            gas_price: Some(0x10.into()),
+            value: None,
+            data: None,
+        };
+        let tx = TransactionRequest {
+            gas_price: Some(0x10.into()),
+            ..Default::default()
+        };
+
+        assert_eq!(
+            serde_json::to_value(&call).unwrap(),
+            json!({"to": "lax1contract", "gasPrice": "0x10"})
+        );
+        assert_eq!(serde_json::to_value(&tx).unwrap()["gasPrice"], "0x10");
+        assert!(serde_json::to_value(&tx)
+            .unwrap()
+            .get("gas_price")
+            .is_none());
+    }
+}
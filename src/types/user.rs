@@ -1,4 +1,4 @@
-use crate::types::{Address, U64};
+use crate::types::{Address, Page, Role, Roles, UserStatus};
 use serde::{Deserialize, Serialize};
 
 /// The user type returned from contract calls.
@@ -16,9 +16,9 @@ pub struct User {
     /// Description
     pub desc: String,
     /// Status
-    pub status: u8,
+    pub status: UserStatus,
     /// Roles of the user
-    pub roles: U64,
+    pub roles: Roles,
     /// Register time
     #[serde(rename = "registerTime")]
     pub register_time: u64,
@@ -27,21 +27,20 @@ pub struct User {
     pub update_time: u64,
 }
 
-/// The user page info returned from contract calls.
-#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
-pub struct UserPage {
-    /// Total counts
-    #[serde(rename = "totalCount")]
-    pub total_count: u32,
-    /// Total pages
-    #[serde(rename = "totalPage")]
-    pub total_page: u32,
-    /// Page number
-    #[serde(rename = "pageNum")]
-    pub page_num: u32,
-    /// Page size
-    #[serde(rename = "page_size")]
-    pub page_size: u32,
-    /// User list
-    pub items: Vec<User>,
+impl User {
+    /// Returns true if the user has the given role.
+    pub fn has_role(&self, role: Role) -> bool {
+        self.roles.has(role)
+    }
+
+    /// Returns true if the user holds at least one of the given roles.
+    ///
+    /// Useful to check up front whether a sender may call a system contract
+    /// method guarded by `required`, instead of waiting for the revert.
+    pub fn has_any_role(&self, required: Roles) -> bool {
+        required.is_empty() || self.roles.intersects(required)
+    }
 }
+
+/// The user page info returned from contract calls.
+pub type UserPage = Page<User>;